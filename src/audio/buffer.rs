@@ -1,14 +1,142 @@
 //! Audio storage.
 
+use crate::error::{Result, VoxlabError};
+
 /// Mono audio buffer.
 #[derive(Clone, Debug, PartialEq)]
 pub struct AudioBuffer {
+    /// Sample rate in Hz.
     pub sample_rate: u32,
+    /// Samples, nominally in `-1.0..=1.0`.
     pub samples: Vec<f32>,
 }
 
 impl AudioBuffer {
+    /// Length of the buffer in samples.
     pub fn len(&self) -> usize {
         self.samples.len()
     }
+
+    /// Whether the buffer has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// How to turn a [`MultiChannelBuffer`] into a mono [`AudioBuffer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Downmix {
+    /// Average all channels.
+    #[default]
+    Average,
+    /// Keep a single channel, counting from zero.
+    Channel(usize),
+}
+
+/// Audio buffer with any number of channels, stored one channel after
+/// another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiChannelBuffer {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Samples of each channel. All channels are the same length.
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl MultiChannelBuffer {
+    /// Splits interleaved samples into `channels` channels. A trailing
+    /// incomplete frame is dropped.
+    ///
+    /// # Panics
+    /// Panics if `channels` is zero.
+    pub fn from_interleaved(sample_rate: u32, channels: usize, samples: &[f32]) -> Self {
+        assert!(channels > 0, "a buffer needs at least one channel");
+        let frames = samples.len() / channels;
+        Self {
+            sample_rate,
+            channels: (0..channels)
+                .map(|c| {
+                    samples
+                        .iter()
+                        .skip(c)
+                        .step_by(channels)
+                        .take(frames)
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Copies a mono buffer into `channels` identical channels.
+    pub fn from_mono(buffer: &AudioBuffer, channels: usize) -> Self {
+        Self {
+            sample_rate: buffer.sample_rate,
+            channels: vec![buffer.samples.clone(); channels],
+        }
+    }
+
+    /// Number of channels.
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Length of the buffer in samples per channel.
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    /// Whether the buffer has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the samples with the channels interleaved.
+    pub fn interleaved(&self) -> Vec<f32> {
+        (0..self.len())
+            .flat_map(|i| self.channels.iter().map(move |channel| channel[i]))
+            .collect()
+    }
+
+    /// Mixes the buffer down to mono.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::InvalidParameter`] if `downmix` picks a channel
+    /// that doesn't exist.
+    pub fn downmix(&self, downmix: Downmix) -> Result<AudioBuffer> {
+        let samples = match downmix {
+            Downmix::Average => {
+                let scale = 1.0 / self.channel_count().max(1) as f32;
+                (0..self.len())
+                    .map(|i| self.channels.iter().map(|channel| channel[i]).sum::<f32>() * scale)
+                    .collect()
+            }
+            Downmix::Channel(c) => self
+                .channels
+                .get(c)
+                .ok_or_else(|| {
+                    VoxlabError::invalid(
+                        "downmix",
+                        format!(
+                            "channel {c} doesn't exist, the buffer has {}",
+                            self.channel_count()
+                        ),
+                    )
+                })?
+                .clone(),
+        };
+        Ok(AudioBuffer {
+            sample_rate: self.sample_rate,
+            samples,
+        })
+    }
+}
+
+impl From<AudioBuffer> for MultiChannelBuffer {
+    fn from(value: AudioBuffer) -> Self {
+        Self {
+            sample_rate: value.sample_rate,
+            channels: vec![value.samples],
+        }
+    }
 }
@@ -1,7 +1,21 @@
 //! Audio importing and storage.
 
 pub mod buffer;
+pub mod resample;
 pub mod wav;
 
+/// A (fractional) MIDI note number; 69 is A4 at 440 Hz.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct MidiNote(pub f32);
+
+impl MidiNote {
+    /// The note at `frequency` Hz.
+    pub fn from_frequency(frequency: f32) -> Self {
+        Self(69.0 + 12.0 * (frequency / 440.0).log2())
+    }
+
+    /// Frequency of the note in Hz.
+    pub fn frequency(self) -> f32 {
+        440.0 * 2.0_f32.powf((self.0 - 69.0) / 12.0)
+    }
+}
@@ -0,0 +1,197 @@
+//! Band-limited sample rate conversion.
+
+use std::f64::consts::PI;
+
+use crate::{
+    audio::buffer::{AudioBuffer, MultiChannelBuffer},
+    error::{Result, positive},
+};
+
+/// Zero crossings of the sinc kernel on either side of its centre, at the
+/// lower of the two rates.
+const ZERO_CROSSINGS: usize = 32;
+
+/// Shape parameter of the Kaiser window applied to the kernel. 8.6 gives
+/// about 90 dB of stopband attenuation.
+const KAISER_BETA: f64 = 8.6;
+
+/// Cutoff as a fraction of the lower Nyquist frequency, leaving room for the
+/// transition band.
+const CUTOFF: f64 = 0.95;
+
+impl AudioBuffer {
+    /// Converts the buffer to `sample_rate` with a Kaiser-windowed sinc
+    /// filter. When downsampling, content above the new Nyquist frequency is
+    /// filtered out rather than aliased.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::InvalidParameter`] if `sample_rate` is zero.
+    ///
+    /// [`VoxlabError::InvalidParameter`]: crate::error::VoxlabError::InvalidParameter
+    pub fn resample(&self, sample_rate: u32) -> Result<AudioBuffer> {
+        positive("sample_rate", sample_rate as f32)?;
+        if sample_rate == self.sample_rate || self.is_empty() {
+            return Ok(AudioBuffer {
+                sample_rate,
+                samples: self.samples.clone(),
+            });
+        }
+
+        let step = f64::from(self.sample_rate) / f64::from(sample_rate);
+        // Cutoff relative to the input rate; the kernel widens by the same
+        // factor when downsampling.
+        let cutoff = CUTOFF * step.recip().min(1.0);
+        let half_width = ZERO_CROSSINGS as f64 / cutoff;
+        let window_norm = bessel_i0(KAISER_BETA);
+
+        let out_len = (self.len() as f64 / step).ceil() as usize;
+        let samples = (0..out_len)
+            .map(|n| {
+                let t = n as f64 * step;
+                let first = (t - half_width).ceil().max(0.0) as usize;
+                let last = ((t + half_width).floor() as usize).min(self.len() - 1);
+                (first..=last)
+                    .map(|k| {
+                        let x = t - k as f64;
+                        let ratio = x / half_width;
+                        let window = bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).max(0.0).sqrt())
+                            / window_norm;
+                        f64::from(self.samples[k]) * cutoff * sinc(cutoff * x) * window
+                    })
+                    .sum::<f64>() as f32
+            })
+            .collect();
+
+        Ok(AudioBuffer {
+            sample_rate,
+            samples,
+        })
+    }
+}
+
+impl MultiChannelBuffer {
+    /// Converts every channel to `sample_rate`, like [`AudioBuffer::resample`].
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::InvalidParameter`] if `sample_rate` is zero.
+    ///
+    /// [`VoxlabError::InvalidParameter`]: crate::error::VoxlabError::InvalidParameter
+    pub fn resample(&self, sample_rate: u32) -> Result<MultiChannelBuffer> {
+        positive("sample_rate", sample_rate as f32)?;
+        Ok(MultiChannelBuffer {
+            sample_rate,
+            channels: self
+                .channels
+                .iter()
+                .map(|channel| {
+                    let buffer = AudioBuffer {
+                        sample_rate: self.sample_rate,
+                        samples: channel.clone(),
+                    };
+                    Ok(buffer.resample(sample_rate)?.samples)
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Normalised sinc, `sin(πx) / πx`.
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Zeroth-order modified Bessel function of the first kind, by its power
+/// series.
+fn bessel_i0(x: f64) -> f64 {
+    let quarter_sq = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..50 {
+        term *= quarter_sq / f64::from(k * k);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::error::VoxlabError;
+
+    fn sine(freq: f32, sample_rate: u32, len: usize) -> AudioBuffer {
+        AudioBuffer {
+            sample_rate,
+            samples: (0..len)
+                .map(|i| (TAU * freq * i as f32 / sample_rate as f32).sin())
+                .collect(),
+        }
+    }
+
+    /// Largest deviation from `expected` away from the edges, where the
+    /// kernel runs off the input.
+    fn max_error(buffer: &AudioBuffer, expected: impl Fn(usize) -> f32) -> f32 {
+        let len = buffer.len();
+        (len / 4..len * 3 / 4)
+            .map(|i| (buffer.samples[i] - expected(i)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn lengths() {
+        let buffer = sine(440.0, 44100, 1000);
+        assert_eq!(buffer.resample(22050).expect("resample").len(), 500);
+        assert_eq!(buffer.resample(48000).expect("resample").len(), 1089);
+        assert_eq!(buffer.resample(44100).expect("resample"), buffer);
+        assert_eq!(buffer.resample(48000).expect("resample").sample_rate, 48000);
+    }
+
+    #[test]
+    fn unity_dc_gain() {
+        let dc = AudioBuffer {
+            sample_rate: 44100,
+            samples: vec![0.5; 4000],
+        };
+        for rate in [8000, 22050, 48000, 96000] {
+            let error = max_error(&dc.resample(rate).expect("resample"), |_| 0.5);
+            assert!(error < 1e-3, "{rate} Hz: error {error}");
+        }
+    }
+
+    #[test]
+    fn keeps_passband() {
+        let resampled = sine(1000.0, 44100, 4000).resample(48000).expect("resample");
+        let error = max_error(&resampled, |i| (TAU * 1000.0 * i as f32 / 48000.0).sin());
+        assert!(error < 1e-3, "error {error}");
+    }
+
+    #[test]
+    fn filters_above_new_nyquist() {
+        let resampled = sine(15000.0, 44100, 4000)
+            .resample(22050)
+            .expect("resample");
+        let error = max_error(&resampled, |_| 0.0);
+        assert!(error < 1e-2, "error {error}");
+    }
+
+    #[test]
+    fn zero_rate_is_an_error() {
+        let buffer = sine(440.0, 44100, 100);
+        assert!(matches!(
+            buffer.resample(0),
+            Err(VoxlabError::InvalidParameter { .. })
+        ));
+        assert!(matches!(
+            MultiChannelBuffer::from(buffer).resample(0),
+            Err(VoxlabError::InvalidParameter { .. })
+        ));
+    }
+}
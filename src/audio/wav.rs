@@ -1,29 +1,55 @@
-//! Importing wav files.
+//! Importing and exporting WAV files.
 
 use std::path::Path;
 
 use hound::WavSpec;
 
-use crate::audio::buffer::AudioBuffer;
+use crate::{
+    audio::buffer::{AudioBuffer, Downmix, MultiChannelBuffer},
+    error::{Result, VoxlabError},
+    nice::Rng,
+};
 
-/// Import a mono WAV file and convert it to a normalized `AudioBuffer`.
+/// Import a WAV file and convert it to a normalized `AudioBuffer`, averaging
+/// the channels of multichannel files.
 ///
 /// Supported formats:
 /// - 16-bit PCM
 /// - 32-bit PCM
 /// - 32-bit float
 ///
-/// Stereo files are rejected.
-pub fn import_wav(path: impl AsRef<Path>) -> hound::Result<AudioBuffer> {
+/// # Errors
+/// Returns an error if the file can't be read or is in another format.
+pub fn import_wav(path: impl AsRef<Path>) -> Result<AudioBuffer> {
+    import_wav_with(path, Downmix::Average)
+}
+
+/// Like [`import_wav`], mixing multichannel files down with `downmix`.
+///
+/// # Errors
+/// Returns an error if the file can't be read, is in another format, or
+/// doesn't have the channel `downmix` picks.
+pub fn import_wav_with(path: impl AsRef<Path>, downmix: Downmix) -> Result<AudioBuffer> {
+    import_wav_multichannel(path)?.downmix(downmix)
+}
+
+/// Import a WAV file with all its channels. See [`import_wav`] for the
+/// supported formats.
+///
+/// # Errors
+/// Returns an error if the file can't be read or is in another format.
+pub fn import_wav_multichannel(path: impl AsRef<Path>) -> Result<MultiChannelBuffer> {
     let mut reader = hound::WavReader::open(path)?;
     let spec = reader.spec();
 
-    if spec.channels != 1 {
-        panic!("only mono WAV files are supported");
+    if spec.channels == 0 {
+        return Err(VoxlabError::UnsupportedFormat(
+            "WAV file has no channels".to_owned(),
+        ));
     }
 
     let samples = match spec.sample_format {
-        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<hound::Result<Vec<_>>>()?,
 
         hound::SampleFormat::Int => {
             let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
@@ -31,30 +57,347 @@ pub fn import_wav(path: impl AsRef<Path>) -> hound::Result<AudioBuffer> {
             reader
                 .samples::<i32>()
                 .map(|s| s.map(|v| v as f32 / max))
-                .collect::<Result<Vec<_>, _>>()?
+                .collect::<hound::Result<Vec<_>>>()?
         }
     };
 
-    Ok(AudioBuffer {
-        sample_rate: spec.sample_rate,
-        samples,
-    })
+    Ok(MultiChannelBuffer::from_interleaved(
+        spec.sample_rate,
+        usize::from(spec.channels),
+        &samples,
+    ))
+}
+
+/// How samples are stored in an exported WAV file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// IEEE floating point. Only 32-bit is supported.
+    #[default]
+    Float,
+    /// Integer PCM, 8, 16, 24 or 32-bit.
+    Int,
+}
+
+/// Noise added when quantising to an integer format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Round to the nearest step. Quiet passages turn the rounding error into
+    /// distortion.
+    None,
+    /// Triangular noise of up to one step either way, which turns the rounding
+    /// error into constant, signal-independent hiss.
+    #[default]
+    Tpdf,
+    /// [`Dither::Tpdf`] with first-order error feedback, which moves the hiss
+    /// towards high frequencies where it's less audible.
+    Shaped,
 }
 
-/// Export a WAV file.
-pub fn export_wav(buffer: AudioBuffer, path: impl AsRef<Path>) -> hound::Result<()> {
+/// Format of an exported WAV file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Bits per sample.
+    pub bits_per_sample: u16,
+    /// Integer or floating point samples.
+    pub sample_format: SampleFormat,
+    /// Sample rate to convert to, or `None` to keep the buffer's.
+    pub sample_rate: Option<u32>,
+    /// Number of channels. A mono buffer is copied to every channel and any
+    /// buffer can be mixed down to mono; other conversions are errors.
+    pub channels: u16,
+    /// Dither for integer formats. Ignored for floating point.
+    pub dither: Dither,
+    /// Seed of the dither noise, so exports are reproducible.
+    pub seed: u64,
+}
+
+impl Default for ExportOptions {
+    /// 32-bit float mono at the buffer's sample rate, with TPDF dither if the
+    /// format is changed to integer.
+    fn default() -> Self {
+        Self {
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+            sample_rate: None,
+            channels: 1,
+            dither: Dither::default(),
+            seed: 0,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Integer PCM with `bits_per_sample` bits and TPDF dither, mono at the
+    /// buffer's sample rate.
+    pub fn pcm(bits_per_sample: u16) -> Self {
+        Self {
+            bits_per_sample,
+            sample_format: SampleFormat::Int,
+            ..Self::default()
+        }
+    }
+
+    /// Checks that the options make sense.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::InvalidParameter`] if the bit depth isn't
+    /// supported for the sample format, or the sample rate or channel count is
+    /// zero.
+    pub fn validate(&self) -> Result<()> {
+        let supported: &[u16] = match self.sample_format {
+            SampleFormat::Float => &[32],
+            SampleFormat::Int => &[8, 16, 24, 32],
+        };
+        if !supported.contains(&self.bits_per_sample) {
+            return Err(VoxlabError::invalid(
+                "bits_per_sample",
+                format!(
+                    "{} isn't supported for {:?} samples, use one of {supported:?}",
+                    self.bits_per_sample, self.sample_format
+                ),
+            ));
+        }
+        if self.sample_rate == Some(0) {
+            return Err(VoxlabError::invalid("sample_rate", "must not be zero"));
+        }
+        if self.channels == 0 {
+            return Err(VoxlabError::invalid("channels", "must not be zero"));
+        }
+        Ok(())
+    }
+}
+
+/// What happened to the signal on export.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExportStats {
+    /// Number of samples written, counting every channel.
+    pub samples: usize,
+    /// Number of samples beyond full scale, counted the same way for every
+    /// format. Integer formats clamp them; floating point keeps them, but
+    /// they'll clip on playback.
+    pub clipped: usize,
+    /// Number of samples within full scale that were clamped anyway, because
+    /// dither pushed them past the largest integer or they sat on full scale
+    /// itself, one step above it. Always zero for floating point.
+    pub clamped: usize,
+    /// Largest absolute sample value before quantisation.
+    pub peak: f32,
+}
+
+impl ExportStats {
+    /// Fraction of the written samples that clipped.
+    pub fn clipped_fraction(&self) -> f32 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.clipped as f32 / self.samples as f32
+        }
+    }
+}
+
+/// Export a mono buffer as a 32-bit float WAV file.
+///
+/// # Errors
+/// Returns an error if the file can't be written.
+pub fn export_wav(buffer: AudioBuffer, path: impl AsRef<Path>) -> Result<ExportStats> {
+    export_wav_with(&buffer.into(), path, &ExportOptions::default())
+}
+
+/// Export a WAV file in the format described by `options`, converting the
+/// sample rate and channel layout first if needed.
+///
+/// # Errors
+/// Returns an error if the options are invalid, the buffer's channels can't be
+/// converted to `options.channels`, or the file can't be written.
+pub fn export_wav_with(
+    buffer: &MultiChannelBuffer,
+    path: impl AsRef<Path>,
+    options: &ExportOptions,
+) -> Result<ExportStats> {
+    options.validate()?;
+    let buffer = with_channels(buffer, usize::from(options.channels))?;
+    let buffer = match options.sample_rate {
+        Some(rate) if rate != buffer.sample_rate => buffer.resample(rate)?,
+        _ => buffer,
+    };
+
     let mut writer = hound::WavWriter::create(
         path,
         WavSpec {
-            channels: 1,
+            channels: options.channels,
             sample_rate: buffer.sample_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
+            bits_per_sample: options.bits_per_sample,
+            sample_format: match options.sample_format {
+                SampleFormat::Float => hound::SampleFormat::Float,
+                SampleFormat::Int => hound::SampleFormat::Int,
+            },
         },
     )?;
-    for sample in buffer.samples {
-        writer.write_sample(sample)?;
+
+    let samples = buffer.interleaved();
+    let mut stats = ExportStats {
+        samples: samples.len(),
+        clipped: samples.iter().filter(|s| s.abs() > 1.0).count(),
+        clamped: 0,
+        peak: samples.iter().fold(0.0, |peak, s| s.abs().max(peak)),
+    };
+
+    match options.sample_format {
+        SampleFormat::Float => {
+            for sample in samples {
+                writer.write_sample(sample)?;
+            }
+        }
+        SampleFormat::Int => {
+            let mut quantiser = Quantiser::new(options, buffer.channel_count());
+            for (i, sample) in samples.into_iter().enumerate() {
+                let (value, clamped) = quantiser.quantise(sample, i % buffer.channel_count());
+                stats.clamped += usize::from(clamped && sample.abs() <= 1.0);
+                writer.write_sample(value)?;
+            }
+        }
     }
+
     writer.finalize()?;
-    Ok(())
+    Ok(stats)
+}
+
+/// Converts `buffer` to `channels` channels.
+fn with_channels(buffer: &MultiChannelBuffer, channels: usize) -> Result<MultiChannelBuffer> {
+    match buffer.channel_count() {
+        n if n == channels => Ok(buffer.clone()),
+        1 => Ok(MultiChannelBuffer::from_mono(
+            &buffer.downmix(Downmix::Channel(0))?,
+            channels,
+        )),
+        _ if channels == 1 => Ok(buffer.downmix(Downmix::Average)?.into()),
+        n => Err(VoxlabError::invalid(
+            "channels",
+            format!("can't convert {n} channels to {channels}"),
+        )),
+    }
+}
+
+/// Turns floating point samples into dithered integers.
+struct Quantiser {
+    /// Value of full scale in steps.
+    scale: f64,
+    /// Smallest integer that can be written.
+    min: f64,
+    /// Largest integer that can be written.
+    max: f64,
+    /// Kind of dither.
+    dither: Dither,
+    /// Source of the dither noise.
+    rng: Rng,
+    /// Quantisation error of the previous sample of each channel, for noise
+    /// shaping.
+    error: Vec<f64>,
+}
+
+impl Quantiser {
+    /// Creates a quantiser for the integer format in `options`.
+    fn new(options: &ExportOptions, channels: usize) -> Self {
+        let scale = f64::from(1u32 << (options.bits_per_sample - 1));
+        Self {
+            scale,
+            min: -scale,
+            max: scale - 1.0,
+            dither: options.dither,
+            rng: Rng::new(options.seed),
+            error: vec![0.0; channels],
+        }
+    }
+
+    /// Quantises `sample` of `channel`, returning the integer and whether it
+    /// had to be clamped.
+    fn quantise(&mut self, sample: f32, channel: usize) -> (i32, bool) {
+        let mut wanted = f64::from(sample) * self.scale;
+        if self.dither == Dither::Shaped {
+            wanted -= self.error[channel];
+        }
+        let noise = match self.dither {
+            Dither::None => 0.0,
+            Dither::Tpdf | Dither::Shaped => {
+                f64::from(self.rng.next_f32()) - f64::from(self.rng.next_f32())
+            }
+        };
+        let rounded = (wanted + noise).round();
+        // The error excludes clamping, which would otherwise be fed back into
+        // the following samples.
+        self.error[channel] = rounded - wanted;
+
+        (
+            rounded.clamp(self.min, self.max) as i32,
+            !(self.min..=self.max).contains(&rounded),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples straddling full scale, in both directions.
+    const SAMPLES: [f32; 8] = [0.0, 0.5, -0.5, 1.0, -1.0, 1.5, -1.5, 2.0];
+
+    /// Exports `SAMPLES` with `options` and returns the stats.
+    fn export(options: &ExportOptions, name: &str) -> ExportStats {
+        let path = std::env::temp_dir().join(format!("voxlab-{}-{name}.wav", std::process::id()));
+        let buffer = MultiChannelBuffer::from_interleaved(8000, 1, &SAMPLES);
+        let stats = export_wav_with(&buffer, &path, options).expect("export");
+        std::fs::remove_file(&path).expect("remove");
+        stats
+    }
+
+    #[test]
+    fn quantiser_clamps_to_integer_range() {
+        let mut quantiser = Quantiser::new(
+            &ExportOptions {
+                dither: Dither::None,
+                ..ExportOptions::pcm(16)
+            },
+            1,
+        );
+        assert_eq!(quantiser.quantise(0.5, 0), (16384, false));
+        assert_eq!(quantiser.quantise(-1.0, 0), (-32768, false));
+        // Full scale is one step above the largest positive integer.
+        assert_eq!(quantiser.quantise(1.0, 0), (32767, true));
+        assert_eq!(quantiser.quantise(-1.5, 0), (-32768, true));
+    }
+
+    #[test]
+    fn clipping_counts_match_across_formats() {
+        let float = export(&ExportOptions::default(), "float");
+        assert_eq!(float.samples, 8);
+        assert_eq!(float.clipped, 3);
+        assert_eq!(float.clamped, 0);
+        assert!((float.peak - 2.0).abs() < 1e-6);
+
+        for dither in [Dither::None, Dither::Tpdf, Dither::Shaped] {
+            let int = export(
+                &ExportOptions {
+                    dither,
+                    ..ExportOptions::pcm(16)
+                },
+                &format!("{dither:?}"),
+            );
+            assert_eq!(int.clipped, float.clipped, "{dither:?}");
+            assert!((int.peak - float.peak).abs() < 1e-6, "{dither:?}");
+        }
+    }
+
+    #[test]
+    fn full_scale_is_clamped_not_clipped() {
+        let stats = export(
+            &ExportOptions {
+                dither: Dither::None,
+                ..ExportOptions::pcm(16)
+            },
+            "full-scale",
+        );
+        assert_eq!(stats.clipped, 3);
+        assert_eq!(stats.clamped, 1);
+        assert!((stats.clipped_fraction() - 3.0 / 8.0).abs() < 1e-6);
+    }
 }
@@ -1,42 +1,61 @@
 //! Crossfade between two buffers.
 
-use std::f32::consts::FRAC_PI_2;
-
 use crate::{
     audio::buffer::AudioBuffer,
     dsp::{
-        psola::{self, Grain, get_avg_period, overlap_add_grain},
+        gci::gci_pitch_marks,
+        psola::{self, get_avg_period, overlap_add_grain},
         window_calc::find_window,
     },
+    error::{Result, VoxlabError},
 };
 
+/// Default crossfade length in samples (50 ms at 44.1 kHz).
 pub const CROSSFADE_TIME: usize = 2205;
 
-const MIN_TRANSITION_PERIODS: usize = 5;
-
 /// Grain-level interpolation.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct GrainInterp {
-    /// Grain index within the target phoneme
-    pub target_grain: usize,
+    /// Length of the fade in samples.
     pub fade_len: usize,
 }
 
-pub fn crossfade(buf1: &AudioBuffer, buf2: &AudioBuffer, interp: &GrainInterp) -> AudioBuffer {
-    let period = get_avg_period(buf1);
+/// Crossfades from the end of the voiced region of `buf1` into the start of
+/// the voiced region of `buf2`, interpolating grain by grain so the pitch
+/// periods stay aligned. `buf2` is resampled to the rate of `buf1` if they
+/// differ.
+///
+/// # Errors
+/// Returns an error if either buffer has no voiced region or no usable pitch
+/// marks.
+pub fn crossfade(
+    buf1: &AudioBuffer,
+    buf2: &AudioBuffer,
+    interp: &GrainInterp,
+) -> Result<AudioBuffer> {
+    let resampled;
+    let buf2 = if buf2.sample_rate == buf1.sample_rate {
+        buf2
+    } else {
+        resampled = buf2.resample(buf1.sample_rate)?;
+        &resampled
+    };
+
+    let period = get_avg_period(buf1)?;
 
     let fade_len = interp.fade_len.max(5 * period);
 
-    let windows_a = find_window(buf1, None);
-    let marks_a = psola::generate_pitch_marks(buf1, &windows_a);
+    let windows_a = find_window(buf1, None)?;
+    let marks_a = whole_marks(&gci_pitch_marks(buf1, &windows_a));
 
-    let (voiced_start_a, voiced_end_a) = find_voiced_region(&buf1).unwrap();
-    let cut_a = find_cut_a(&marks_a, voiced_end_a, interp.fade_len, period).unwrap();
+    let (_, voiced_end_a) = find_voiced_region(buf1).ok_or(VoxlabError::NoVoicedRegion)?;
+    let cut_a = find_cut_a(&marks_a, voiced_end_a, interp.fade_len, period)
+        .ok_or(VoxlabError::NoPitchMarks)?;
 
-    let windows_b = find_window(buf2, None);
-    let marks_b = psola::generate_pitch_marks(buf2, &windows_b);
+    let windows_b = find_window(buf2, None)?;
+    let marks_b = whole_marks(&gci_pitch_marks(buf2, &windows_b));
 
-    let (voiced_start_b, voiced_end_b) = find_voiced_region(&buf2).unwrap();
+    let (voiced_start_b, _) = find_voiced_region(buf2).ok_or(VoxlabError::NoVoicedRegion)?;
     let start_b = find_start_b(
         &marks_b,
         voiced_start_b,
@@ -44,27 +63,18 @@ pub fn crossfade(buf1: &AudioBuffer, buf2: &AudioBuffer, interp: &GrainInterp) -
         period,
         buf2.samples.len(),
     )
-    .unwrap();
+    .ok_or(VoxlabError::NoPitchMarks)?;
 
-    assert!(cut_a >= interp.fade_len);
-    assert!(cut_a <= voiced_end_a);
-
-    assert!(start_b >= voiced_start_b);
-    assert!(start_b + interp.fade_len <= buf2.samples.len());
+    phase_aligned_crossfade(buf1, buf2, cut_a, start_b, period, GrainInterp { fade_len })
+}
 
-    phase_aligned_crossfade(
-        buf1,
-        buf2,
-        cut_a,
-        start_b,
-        period,
-        GrainInterp {
-            target_grain: interp.target_grain,
-            fade_len,
-        },
-    )
+/// Rounds pitch marks to whole samples.
+fn whole_marks(marks: &[f32]) -> Vec<usize> {
+    marks.iter().map(|&mark| mark.round() as usize).collect()
 }
 
+/// Finds the last pitch mark that leaves a full period before `voiced_end` and
+/// at least `fade_len` samples before it.
 pub fn find_cut_a(
     marks: &[usize],
     voiced_end: usize,
@@ -78,6 +88,8 @@ pub fn find_cut_a(
         .find(|&m| m + period <= voiced_end && m >= fade_len)
 }
 
+/// Finds the first pitch mark in the voiced region that leaves room for the
+/// fade and a full period before `buffer_len`.
 pub fn find_start_b(
     marks: &[usize],
     voiced_start: usize,
@@ -91,6 +103,13 @@ pub fn find_start_b(
         .find(|&m| m >= voiced_start && m + fade_len + period <= buffer_len)
 }
 
+/// Joins `buf1` up to `cut_a` to `buf2` from `b_start`, interpolating grains
+/// over `interp.fade_len` samples. `b_start` is first moved by up to a period to
+/// line its phase up with `buf1`.
+///
+/// # Errors
+/// Returns an error if the sample rates differ, the fade doesn't fit in
+/// `buf1`, or either buffer doesn't have enough grains for the fade.
 pub fn phase_aligned_crossfade(
     buf1: &AudioBuffer,
     buf2: &AudioBuffer,
@@ -98,59 +117,69 @@ pub fn phase_aligned_crossfade(
     b_start: usize,
     period: usize,
     interp: GrainInterp,
-) -> AudioBuffer {
-    assert_eq!(buf1.sample_rate, buf2.sample_rate);
+) -> Result<AudioBuffer> {
+    if buf1.sample_rate != buf2.sample_rate {
+        return Err(VoxlabError::invalid(
+            "buf2",
+            format!(
+                "sample rate {} doesn't match {}",
+                buf2.sample_rate, buf1.sample_rate
+            ),
+        ));
+    }
 
     let fade_len = interp.fade_len;
 
     let b_start = align_b_start(&buf1.samples, &buf2.samples, cut_a, b_start, period);
 
-    let a_start = cut_a - fade_len;
+    let a_start = cut_a
+        .checked_sub(fade_len)
+        .ok_or_else(|| VoxlabError::invalid("interp", "fade is longer than the first buffer"))?;
 
-    let mut out =
-        Vec::with_capacity(a_start + fade_len + (buf2.samples.len() - (b_start + fade_len)));
+    let mut out = Vec::with_capacity(
+        a_start + fade_len + buf2.samples.len().saturating_sub(b_start + fade_len),
+    );
 
-    let windows_a = find_window(buf1, None);
-    let marks_a = psola::generate_pitch_marks(buf1, &windows_a);
-    let grains_a = psola::extract_grains(&buf1, &marks_a, &windows_a);
+    let windows_a = find_window(buf1, None)?;
+    let marks_a = gci_pitch_marks(buf1, &windows_a);
+    let grains_a = psola::extract_grains(buf1, &marks_a, &windows_a);
 
-    let windows_b = find_window(buf2, None);
-    let marks_b = psola::generate_pitch_marks(buf2, &windows_b);
-    let grains_b = psola::extract_grains(&buf2, &marks_b, &windows_b);
+    let windows_b = find_window(buf2, None)?;
+    let marks_b = gci_pitch_marks(buf2, &windows_b);
+    let grains_b = psola::extract_grains(buf2, &marks_b, &windows_b);
 
-    let fade_len = fade_len / dbg!(period + 1);
+    let fade_len = fade_len / (period + 1);
     let a_start = a_start / (period + 1);
     let b_start = b_start / (period + 1);
 
-    assert!(dbg!(a_start) + dbg!(fade_len) <= dbg!(grains_a.len()));
-    assert!(b_start + fade_len <= grains_b.len());
-
-    for i in 0..a_start {
-        out.push(grains_a[i].samples.to_vec());
+    if a_start + fade_len > grains_a.len() || b_start + fade_len > grains_b.len() {
+        return Err(VoxlabError::NoPitchMarks);
     }
 
+    out.extend(grains_a[..a_start].iter().map(|g| g.samples.to_vec()));
+
     for k in 0..fade_len {
-        let t = transition_t(k, dbg!(fade_len));
-        let ga = &grains_a[dbg!(dbg!(a_start) + dbg!(k))];
+        let t = transition_t(k, fade_len);
+        let ga = &grains_a[a_start + k];
         let gb = &grains_b[k];
 
         out.push(psola::lerp_grain(ga, gb, t));
     }
 
-    for i in fade_len..grains_b.len() {
-        out.push(grains_b[i].samples.to_vec());
-    }
+    out.extend(grains_b[fade_len..].iter().map(|g| g.samples.to_vec()));
 
-    AudioBuffer {
+    Ok(AudioBuffer {
         sample_rate: buf1.sample_rate,
         samples: overlap_add_grain(
-            out,
-            &[marks_a, marks_b].concat(),
-            a_start + fade_len + (buf2.samples.len() - (b_start + fade_len)),
+            &out,
+            &whole_marks(&[marks_a, marks_b].concat()),
+            a_start + fade_len + buf2.samples.len().saturating_sub(b_start + fade_len),
         ),
-    }
+    })
 }
 
+/// Smoothstep blend factor for grain `k` of a `len`-grain transition, pinned to
+/// exactly one half in the middle.
 fn transition_t(k: usize, len: usize) -> f32 {
     let mid = len / 2;
 
@@ -162,6 +191,8 @@ fn transition_t(k: usize, len: usize) -> f32 {
     }
 }
 
+/// Moves `start_b` by up to a period in either direction to where `b`
+/// correlates best with the last period of `a` before `cut_a`.
 pub fn align_b_start(a: &[f32], b: &[f32], cut_a: usize, start_b: usize, period: usize) -> usize {
     let n = period.max(1); // template length
 
@@ -198,15 +229,14 @@ pub fn align_b_start(a: &[f32], b: &[f32], cut_a: usize, start_b: usize, period:
     best
 }
 
+/// Unnormalised cross-correlation of two equally long slices.
 pub fn corr(a: &[f32], b: &[f32]) -> f32 {
     debug_assert_eq!(a.len(), b.len());
-    let mut s = 0.0;
-    for i in 0..a.len() {
-        s += a[i] * b[i];
-    }
-    s
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
 }
 
+/// Finds the region of `buf` louder than 5% of its peak RMS, in 10 ms
+/// frames. Returns `None` if the buffer is silent or shorter than a frame.
 pub fn find_voiced_region(buf: &AudioBuffer) -> Option<(usize, usize)> {
     let win = (0.010 * buf.sample_rate as f32) as usize; // 10 ms
     let hop = (0.005 * buf.sample_rate as f32) as usize; // 5 ms
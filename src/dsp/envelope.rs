@@ -0,0 +1,54 @@
+//! Sampled control envelopes.
+
+use crate::nice::lerp;
+
+/// A control signal (pitch ratio, stretch, gain, ...) sampled at a fixed
+/// interval.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    /// Time between consecutive values, in seconds.
+    pub step: f32,
+    /// The sampled values. The first value is at time zero.
+    pub values: Vec<f32>,
+}
+
+impl Envelope {
+    /// Creates an envelope from already sampled values.
+    ///
+    /// # Panics
+    /// Panics if `step` isn't positive.
+    pub fn new(step: f32, values: Vec<f32>) -> Self {
+        assert!(step > 0.0);
+        Self { step, values }
+    }
+
+    /// Samples `func` every `step` seconds from zero up to and including
+    /// `duration`.
+    ///
+    /// # Panics
+    /// Panics if `step` isn't positive.
+    pub fn sample(duration: f32, step: f32, mut func: impl FnMut(f32) -> f32) -> Self {
+        assert!(step > 0.0);
+        let count = (duration / step).ceil() as usize + 1;
+        Self {
+            step,
+            values: (0..count).map(|i| func(i as f32 * step)).collect(),
+        }
+    }
+
+    /// Returns the value at `t` seconds, linearly interpolated between samples
+    /// and held at either end.
+    pub fn at(&self, t: f32) -> f32 {
+        let Some(&last) = self.values.last() else {
+            return 0.0;
+        };
+
+        let pos = (t / self.step).max(0.0);
+        let idx = pos.floor() as usize;
+        if idx + 1 >= self.values.len() {
+            return last;
+        }
+
+        lerp(self.values[idx], self.values[idx + 1], pos - idx as f32)
+    }
+}
@@ -0,0 +1,133 @@
+//! Pitch marks on glottal closure instants.
+
+use crate::{audio::buffer::AudioBuffer, dsp::psola::lag_at};
+
+/// How far either side of its predicted position the next mark is searched
+/// for, as a fraction of the local period.
+const SEARCH_RADIUS: f32 = 0.3;
+
+/// Places pitch marks on the excitation peaks of `buffer`, with sub-sample
+/// positions.
+///
+/// The first mark is the strongest peak in the first period of the first
+/// analysis window. Every following mark is the strongest peak within 30% of
+/// a period either side of one period after the previous mark, so the marks
+/// follow the waveform instead of drifting by the rounding error of the lag.
+/// Peaks are taken in whichever polarity has the largest excursion and refined
+/// by parabolic interpolation.
+pub fn gci_pitch_marks(buffer: &AudioBuffer, windows: &[(usize, usize)]) -> Vec<f32> {
+    let samples = &buffer.samples;
+    let mut marks = Vec::new();
+    let Some(&(first_start, first_lag)) = windows.first() else {
+        return marks;
+    };
+
+    let (min, max) = samples
+        .iter()
+        .fold((0.0f32, 0.0f32), |(min, max), &s| (min.min(s), max.max(s)));
+    let polarity = if max >= -min { 1.0 } else { -1.0 };
+
+    let Some(mut mark) = peak(
+        samples,
+        polarity,
+        first_start,
+        first_start + first_lag.max(1),
+    ) else {
+        return marks;
+    };
+
+    loop {
+        marks.push(mark as f32 + peak_offset(samples, polarity, mark));
+
+        let lag = lag_at(mark, windows).max(1);
+        let radius = ((lag as f32 * SEARCH_RADIUS) as usize).max(1);
+        let predicted = mark + lag;
+        let start = predicted.saturating_sub(radius).max(mark + 1);
+        match peak(samples, polarity, start, predicted + radius + 1) {
+            Some(next) => mark = next,
+            None => break,
+        }
+    }
+
+    marks
+}
+
+/// Index of the largest sample in `start..end` (clamped to `samples`) after
+/// multiplying by `polarity`, or `None` if the range is empty.
+fn peak(samples: &[f32], polarity: f32, start: usize, end: usize) -> Option<usize> {
+    let end = end.min(samples.len());
+    (start..end).max_by(|&a, &b| (samples[a] * polarity).total_cmp(&(samples[b] * polarity)))
+}
+
+/// Sub-sample offset of the peak at `i`, in `-0.5..=0.5`, from a parabola
+/// through it and its neighbours.
+fn peak_offset(samples: &[f32], polarity: f32, i: usize) -> f32 {
+    let (Some(&prev), Some(&cur), Some(&next)) = (
+        i.checked_sub(1).and_then(|i| samples.get(i)),
+        samples.get(i),
+        samples.get(i + 1),
+    ) else {
+        return 0.0;
+    };
+    let (prev, cur, next) = (prev * polarity, cur * polarity, next * polarity);
+
+    let curvature = prev - 2.0 * cur + next;
+    if curvature < 0.0 {
+        (0.5 * (prev - next) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gaussian pulses `period` samples apart, the first at `phase`, scaled
+    /// by `sign`.
+    fn pulse_train(period: f32, phase: f32, sign: f32, len: usize) -> AudioBuffer {
+        let samples = (0..len)
+            .map(|i| {
+                let i = i as f32;
+                let k = ((i - phase) / period).round();
+                let d = (i - phase - k * period) / 3.0;
+                sign * (-d * d).exp()
+            })
+            .collect();
+        AudioBuffer {
+            sample_rate: 44100,
+            samples,
+        }
+    }
+
+    fn assert_on_pulses(marks: &[f32], period: f32, phase: f32, count: usize) {
+        assert_eq!(marks.len(), count, "{marks:?}");
+        for (k, &mark) in marks.iter().enumerate() {
+            let expected = phase + k as f32 * period;
+            assert!(
+                (mark - expected).abs() < 0.1,
+                "mark {k} at {mark}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn marks_follow_fractional_period() {
+        let buffer = pulse_train(100.4, 37.3, 1.0, 5000);
+        let marks = gci_pitch_marks(&buffer, &[(0, 100)]);
+        // Pulses at 37.3, 137.7, ... up to the last one before the end.
+        assert_on_pulses(&marks, 100.4, 37.3, 50);
+    }
+
+    #[test]
+    fn marks_follow_negative_pulses() {
+        let buffer = pulse_train(80.25, 12.6, -1.0, 4000);
+        let marks = gci_pitch_marks(&buffer, &[(0, 80), (2000, 81)]);
+        assert_on_pulses(&marks, 80.25, 12.6, 50);
+    }
+
+    #[test]
+    fn no_windows_no_marks() {
+        assert!(gci_pitch_marks(&pulse_train(100.0, 0.0, 1.0, 1000), &[]).is_empty());
+    }
+}
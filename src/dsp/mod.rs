@@ -1,19 +1,28 @@
 //! Audio abuse.
 
 pub mod crossfade;
+pub mod envelope;
+pub mod gci;
 pub mod pitch;
 pub mod psola;
 pub mod stretch;
+pub mod voicing;
 pub mod window;
 pub mod window_calc;
+pub mod yin;
 
+/// One-pole low-pass filter; `alpha` is the smoothing factor in `0.0..=1.0`.
 pub fn low_pass(values: Vec<f32>, alpha: f32) -> Vec<f32> {
-    let mut out = vec![0.0f32; values.len()];
-    out[0] = values[0];
+    let Some(&first) = values.first() else {
+        return values;
+    };
 
-    for i in 1..values.len() {
-        out[i] = out[i - 1] + alpha * (values[i] - out[i - 1]);
-    }
-
-    out
+    let mut prev = first;
+    values
+        .into_iter()
+        .map(|v| {
+            prev += alpha * (v - prev);
+            prev
+        })
+        .collect()
 }
@@ -2,12 +2,21 @@
 
 use plotters::style::{GREEN, full_palette::ORANGE};
 
-use crate::{audio::buffer::AudioBuffer, dsp::low_pass, nice::lerp, plotting::Plot};
+use crate::{
+    audio::buffer::AudioBuffer,
+    dsp::low_pass,
+    error::{Result, VoxlabError, positive},
+    nice::lerp,
+    plotting::Plot,
+};
 
 /// Naive pitch shift.
+///
+/// # Errors
+/// Returns an error if `ratio` isn't positive.
 #[deprecated = "use PSOLA"]
-pub fn pitch_shift(input: &AudioBuffer, ratio: f32) -> AudioBuffer {
-    assert!(ratio > 0.0);
+pub fn pitch_shift(input: &AudioBuffer, ratio: f32) -> Result<AudioBuffer> {
+    positive("ratio", ratio)?;
 
     let in_len = input.samples.len();
     let out_len = (in_len as f32 / ratio).floor() as usize;
@@ -25,25 +34,31 @@ pub fn pitch_shift(input: &AudioBuffer, ratio: f32) -> AudioBuffer {
         out.push(lerp(s0, s1, frac));
     }
 
-    AudioBuffer {
+    Ok(AudioBuffer {
         sample_rate: input.sample_rate,
         samples: out,
-    }
+    })
 }
 
-#[deprecated = "use PSOLA"]
+/// Resamples `input` with a time-varying ratio, drawing the ratio curve to
+/// `plot`.
+///
+/// # Errors
+/// Returns an error if the ratio isn't positive or plotting fails.
+#[deprecated = "use psola::psola_curve"]
 pub fn pitch_glide(
     input: &AudioBuffer,
     mut ratio: impl FnMut(f32) -> f32,
     plot: &mut Plot<'_>,
-) -> AudioBuffer {
+) -> Result<AudioBuffer> {
     let in_len = input.samples.len();
 
     let mut out = Vec::with_capacity(in_len);
 
     let mut i = 0.0f32;
 
-    plot.plot(&mut ratio, &GREEN, "raw pitch glide").unwrap();
+    plot.plot(&mut ratio, &GREEN, "raw pitch glide")
+        .map_err(|err| VoxlabError::Plot(err.to_string()))?;
 
     let mut pitch_vals = Vec::with_capacity(in_len);
 
@@ -61,13 +76,12 @@ pub fn pitch_glide(
         &ORANGE,
         "filtered pitch glide",
     )
-    .unwrap();
+    .map_err(|err| VoxlabError::Plot(err.to_string()))?;
 
     let mut pitch_i = 0usize;
     while i < in_len as f32 {
-        let ratio = pitch_vals[pitch_i];
+        let ratio = positive("ratio", pitch_vals[pitch_i])?;
         // println!("{i} increasing by {ratio}");
-        assert!(ratio > 0.0);
         i += ratio;
         pitch_i += 1;
 
@@ -84,8 +98,8 @@ pub fn pitch_glide(
         out.push(lerp(s0, s1, frac));
     }
 
-    AudioBuffer {
+    Ok(AudioBuffer {
         sample_rate: input.sample_rate,
         samples: out,
-    }
+    })
 }
@@ -2,10 +2,26 @@
 
 use crate::{
     audio::buffer::AudioBuffer,
-    dsp::{window::hann, window_calc::find_window},
+    dsp::{
+        gci::gci_pitch_marks,
+        voicing::{Voicing, VoicingOptions, segment_frames, voicing_at},
+        window::hann,
+        window_calc::{
+            AnalysisOptions, analyse_pitch, find_window, find_window_with, frame_windows,
+        },
+    },
+    error::{Result, VoxlabError, positive},
     plotting::Plot,
 };
 
+/// Length of the shortest input PSOLA changes, in analysis windows. Shorter
+/// input is returned as is.
+const MIN_WINDOWS: usize = 2;
+
+/// Places pitch marks from the first analysis window onwards, each one lag
+/// after the last, using the lag of the window the previous mark falls in.
+/// The marks drift with the rounding error of the lag and aren't aligned to
+/// the waveform; see [`gci_pitch_marks`].
 pub fn generate_pitch_marks(buffer: &AudioBuffer, windows: &[(usize, usize)]) -> Vec<usize> {
     let mut marks = Vec::new();
 
@@ -54,13 +70,21 @@ pub fn first_mark_geq(marks: &[usize], bound: usize) -> Option<usize> {
     marks.get(i).copied()
 }
 
+/// A two-period slice of a buffer centred on a pitch mark.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Grain<'a> {
-    pub center: usize,
+    /// Pitch mark the grain is centred on, with sub-sample precision.
+    pub center: f32,
+    /// Pitch period at the mark, in samples.
     pub period: usize,
+    /// Index of the grain's first sample in the buffer.
+    pub start: usize,
+    /// The grain's samples, unwindowed.
     pub samples: &'a [f32],
 }
 
+/// Linearly interpolates two grains by `t` after removing their DC offsets,
+/// then windows the result. The output is as long as the shorter grain.
 pub fn lerp_grain(a: &Grain<'_>, b: &Grain<'_>, t: f32) -> Vec<f32> {
     let (mut a, mut b) = (a.samples.to_vec(), b.samples.to_vec());
 
@@ -68,20 +92,16 @@ pub fn lerp_grain(a: &Grain<'_>, b: &Grain<'_>, t: f32) -> Vec<f32> {
     remove_dc(&mut b);
 
     let n = a.len().min(b.len());
-    let mut out = Vec::with_capacity(n);
-
-    for i in 0..n {
-        out.push(a[i] * (1.0 - t) + b[i] * t);
-    }
-
-    let hann = hann(out.len());
-    for i in 0..(out.len()) {
-        out[i] *= hann[i].powf(0.8);
-    }
+    let hann = hann(n);
 
-    out
+    a.iter()
+        .zip(&b)
+        .zip(&hann)
+        .map(|((a, b), w)| (a * (1.0 - t) + b * t) * w.powf(0.8))
+        .collect()
 }
 
+/// Subtracts the mean from a grain.
 fn remove_dc(g: &mut [f32]) {
     let mean = g.iter().copied().sum::<f32>() / g.len() as f32;
     for x in g {
@@ -89,33 +109,72 @@ fn remove_dc(g: &mut [f32]) {
     }
 }
 
-pub fn overlap_add_grain(mut grains: Vec<Vec<f32>>, marks: &[usize], out_len: usize) -> Vec<f32> {
-    // debug_assert!(grains.iter().all(|g| g.len() == grains[0].len()));
-
+/// Overlap-adds already windowed grains centred on `marks` into a buffer of
+/// `out_len` samples, normalising by the summed window weight.
+pub fn overlap_add_grain(grains: &[Vec<f32>], marks: &[usize], out_len: usize) -> Vec<f32> {
     let mut out = vec![0.0; out_len];
     let mut weight = vec![0.0; out_len];
 
     for (g, &m) in grains.iter().zip(marks.iter()) {
-        let start = m.saturating_sub(g.len() / 2);
+        let start = m.saturating_sub(g.len() / 2).min(out_len);
         let win = hann(g.len());
-        for i in 0..g.len() {
-            let idx = start + i;
-            if idx < out_len {
-                out[idx] += g[i];
-                weight[idx] += win[i].powf(0.8);
-            }
+        for ((o, w), (s, win)) in out[start..]
+            .iter_mut()
+            .zip(&mut weight[start..])
+            .zip(g.iter().zip(&win))
+        {
+            *o += s;
+            *w += win.powf(0.8);
         }
     }
 
-    for i in 0..out_len {
-        if weight[i] > 1e-6 {
-            out[i] /= weight[i];
+    for (o, &w) in out.iter_mut().zip(&weight) {
+        if w > 1e-6 {
+            *o /= w;
         }
     }
 
     out
 }
 
+/// Overlap-adds `grain`, windowed by `window`, into `out` so that its pitch
+/// mark lands on `at`, and adds the window to `weight`. A mark landing between
+/// two samples is spread over both by linear interpolation. Both buffers grow
+/// to fit the grain; anything before the start is dropped.
+fn overlap_add(
+    out: &mut Vec<f32>,
+    weight: &mut Vec<f32>,
+    grain: &Grain<'_>,
+    window: &[f32],
+    at: f32,
+) {
+    let shift = at - (grain.center - grain.start as f32);
+    let whole = shift.floor();
+    let frac = shift - whole;
+    let whole = whole as isize;
+
+    let end = (whole + grain.samples.len() as isize + 1).max(0) as usize;
+    if end > out.len() {
+        out.resize(end, 0.0);
+        weight.resize(end, 0.0);
+    }
+
+    for (i, (&v, &w)) in grain.samples.iter().zip(window).enumerate() {
+        let idx = whole + i as isize;
+        for (idx, share) in [(idx, 1.0 - frac), (idx + 1, frac)] {
+            if let Ok(idx) = usize::try_from(idx) {
+                out[idx] += v * w * share;
+                weight[idx] += w * share;
+            }
+        }
+    }
+}
+
+/// Returns the lag of the last analysis window starting at or before `pos`,
+/// or of the first window if there is none.
+///
+/// # Panics
+/// Panics if `windows` is empty.
 pub fn lag_at(pos: usize, windows: &[(usize, usize)]) -> usize {
     let mut lag = windows[0].1;
     for &(wpos, wlag) in windows {
@@ -128,16 +187,20 @@ pub fn lag_at(pos: usize, windows: &[(usize, usize)]) -> usize {
     lag
 }
 
+/// Cuts a grain of two periods around every pitch mark that isn't too close to
+/// either end of the buffer. Marks may be fractional; the grain starts a
+/// period before the nearest sample.
 pub fn extract_grains<'a>(
     buffer: &'a AudioBuffer,
-    marks: &[usize],
+    marks: &[f32],
     windows: &[(usize, usize)],
 ) -> Vec<Grain<'a>> {
     let mut grains = Vec::new();
     let samples = &buffer.samples;
     let len = samples.len();
 
-    for &mark in marks {
+    for &center in marks {
+        let mark = center.round().max(0.0) as usize;
         let period = lag_at(mark, windows);
         let half = period;
 
@@ -148,46 +211,70 @@ pub fn extract_grains<'a>(
         let start = mark - half;
         let end = mark + half;
 
-        let grain_samples = &samples[start..end];
-
         grains.push(Grain {
-            center: mark,
+            center,
             period,
-            samples: grain_samples,
+            start,
+            samples: &samples[start..end],
         });
     }
 
     grains
 }
 
-/// Returns the period as a number of samples.
-pub fn get_avg_period(input: &AudioBuffer) -> usize {
-    let windows = find_window(input, None);
-    let marks = generate_pitch_marks(input, &windows);
+/// Returns the period as a number of samples, estimated with the default
+/// [`AnalysisOptions`].
+///
+/// # Errors
+/// Returns [`VoxlabError::NoPitchMarks`] if no grains can be extracted.
+pub fn get_avg_period(input: &AudioBuffer) -> Result<usize> {
+    get_avg_period_with(input, &AnalysisOptions::default())
+}
+
+/// Like [`get_avg_period`], analysing with `options`.
+///
+/// # Errors
+/// Returns [`VoxlabError::NoPitchMarks`] if no grains can be extracted, or an
+/// error if the options are invalid.
+pub fn get_avg_period_with(input: &AudioBuffer, options: &AnalysisOptions) -> Result<usize> {
+    let windows = find_window_with(input, options, None)?;
+    let marks = gci_pitch_marks(input, &windows);
     let grains = extract_grains(input, &marks, &windows);
 
-    grains.iter().map(|g| g.period).sum::<usize>() / grains.len()
+    if grains.is_empty() {
+        return Err(VoxlabError::NoPitchMarks);
+    }
+
+    Ok(grains.iter().map(|g| g.period).sum::<usize>() / grains.len())
 }
 
+/// PSOLA with a fixed pitch ratio and stretch factor. Unlike [`psola_curve`],
+/// the grain spacing is scaled by the stretch, so stretching also lowers the
+/// pitch.
+///
+/// # Errors
+/// Returns an error if either factor isn't positive or plotting fails.
 pub fn psola_constant(
     input: &AudioBuffer,
     pitch_ratio: f32,
     time_stretch: f32,
     plot: Option<&mut Plot>,
-) -> AudioBuffer {
-    let analysis_windows = &find_window(input, plot);
-
-    assert!(pitch_ratio > 0.0);
-    assert!(time_stretch > 0.0);
-    if input.samples.len() < 2048 || analysis_windows.is_empty() {
-        return input.clone();
+) -> Result<AudioBuffer> {
+    let analysis_windows = &find_window(input, plot)?;
+
+    positive("pitch_ratio", pitch_ratio)?;
+    positive("time_stretch", time_stretch)?;
+    if input.samples.len() < MIN_WINDOWS * AnalysisOptions::default().window
+        || analysis_windows.is_empty()
+    {
+        return Ok(input.clone());
     }
 
-    let marks = generate_pitch_marks(input, analysis_windows);
+    let marks = gci_pitch_marks(input, analysis_windows);
 
     let grains = extract_grains(input, &marks, analysis_windows);
     if grains.is_empty() {
-        return input.clone();
+        return Ok(input.clone());
     }
 
     let in_len = input.samples.len();
@@ -198,35 +285,20 @@ pub fn psola_constant(
     let mut overlap_count = vec![0.0f32; out_len];
 
     let mut out_center_f = grains[0].period as f32;
-    for (gi, grain) in grains.iter().enumerate() {
-        // let t = if grains.len() <= 1 {
-        //     0.0
-        // } else {
-        //     gi as f32 / (grains.len() - 1) as f32
-        // };
-
+    for grain in &grains {
         let r = pitch_ratio;
         let s = time_stretch;
 
         let new_period = (grain.period as f32 / r).max(1.0);
         let grain_len = grain.samples.len();
 
-        let w = hann(grain_len);
-
-        let out_center = out_center_f.round() as isize;
-        let half = (grain_len / 2) as isize;
-        let start = out_center - half;
-
-        for i in 0..grain_len {
-            let out_i = start + i as isize;
-            if out_i < 0 || out_i as usize >= out.len() {
-                continue;
-            }
-            let idx = out_i as usize;
-            let v = grain.samples[i] * w[i];
-            out[idx] += v;
-            overlap_count[idx] += w[i];
-        }
+        overlap_add(
+            &mut out,
+            &mut overlap_count,
+            grain,
+            &hann(grain_len),
+            out_center_f,
+        );
 
         out_center_f += new_period * s;
 
@@ -235,10 +307,9 @@ pub fn psola_constant(
         }
     }
 
-    for i in 0..out.len() {
-        let d = overlap_count[i];
+    for (v, &d) in out.iter_mut().zip(&overlap_count) {
         if d > 1e-6 {
-            out[i] /= d;
+            *v /= d;
         }
     }
 
@@ -248,18 +319,231 @@ pub fn psola_constant(
     }
     out.truncate(trim.max(1));
 
-    let mut peak = 0.0f32;
-    for &s in &out {
-        peak = peak.max(s.abs());
+    Ok(AudioBuffer {
+        sample_rate: input.sample_rate,
+        samples: out,
+    })
+}
+
+/// Overlap-adds the input before `grain` (the `head`) or after it, placing it
+/// where `grain` is placed at `at`. The window is flat away from the grain and
+/// complements the grain's own window over its half nearest the copied input,
+/// so the input is reproduced exactly where the grain is unchanged.
+fn head_or_tail(
+    out: &mut Vec<f32>,
+    weight: &mut Vec<f32>,
+    input: &AudioBuffer,
+    grain: &Grain<'_>,
+    head: bool,
+    at: f32,
+) {
+    let grain_window = hann(grain.samples.len());
+    let mid = grain.start + grain.period;
+    let (start, samples) = if head {
+        (0, &input.samples[..mid])
+    } else {
+        (mid, &input.samples[mid..])
+    };
+    let window = (start..start + samples.len())
+        .map(|i| {
+            i.checked_sub(grain.start)
+                .and_then(|k| grain_window.get(k))
+                .map_or(1.0, |w| 1.0 - w)
+        })
+        .collect::<Vec<_>>();
+
+    overlap_add(
+        out,
+        weight,
+        &Grain {
+            start,
+            samples,
+            ..*grain
+        },
+        &window,
+        at,
+    );
+}
+
+/// Index of the grain whose center is closest to `pos`.
+fn grain_at(grains: &[Grain<'_>], pos: f32) -> usize {
+    let i = grains.partition_point(|g| g.center < pos);
+    if i == 0 {
+        0
+    } else if i >= grains.len() {
+        grains.len() - 1
+    } else if pos - grains[i - 1].center <= grains[i].center - pos {
+        i - 1
+    } else {
+        i
+    }
+}
+
+/// Time-varying PSOLA. Both `pitch_ratio` and `time_stretch` take the position
+/// on the output timeline in seconds and return the pitch ratio and stretch
+/// factor to apply there. A sampled [`Envelope`] can be used through a closure
+/// such as `|t| env.at(t)`.
+///
+/// Unlike [`psola_constant`], pitch and duration are independent: synthesis
+/// marks are spaced by the shifted period, while the analysis position advances
+/// by that spacing divided by the stretch factor. Grains are cut around
+/// `marks`, the pitch marks of `input` (see [`gci_pitch_marks`]), and placed
+/// with sub-sample precision. Their periods come from an analysis with
+/// `analysis`; grains outside voiced segments (see [`segment_frames`]) are
+/// only stretched, never pitch-shifted. The input before the first grain and
+/// after the last is copied through unchanged, crossfaded with the grain next
+/// to it. Input shorter than two analysis windows is returned as is.
+///
+/// # Errors
+/// Returns an error if either closure returns a non-positive factor, the
+/// analysis options are invalid or plotting fails.
+///
+/// [`Envelope`]: crate::dsp::envelope::Envelope
+pub fn psola_curve(
+    input: &AudioBuffer,
+    analysis: &AnalysisOptions,
+    marks: &[f32],
+    mut pitch_ratio: impl FnMut(f32) -> f32,
+    mut time_stretch: impl FnMut(f32) -> f32,
+    plot: Option<&mut Plot>,
+) -> Result<AudioBuffer> {
+    let frames = analyse_pitch(input, analysis, plot)?;
+    let analysis_windows = &frame_windows(&frames);
+
+    if input.samples.len() < MIN_WINDOWS * analysis.window || analysis_windows.is_empty() {
+        return Ok(input.clone());
+    }
+
+    let segments = segment_frames(input, &frames, analysis, &VoicingOptions::default());
+    let grains = extract_grains(input, marks, analysis_windows);
+    let (Some(first), Some(last)) = (grains.first(), grains.last()) else {
+        return Ok(input.clone());
+    };
+
+    let sample_rate = input.sample_rate as f32;
+    let last_center = last.center;
+
+    let mut out = Vec::new();
+    let mut overlap_count = Vec::new();
+
+    // The input before the first grain is copied through as is, fading into
+    // the first grain over its rising half.
+    head_or_tail(
+        &mut out,
+        &mut overlap_count,
+        input,
+        first,
+        true,
+        first.center,
+    );
+
+    let mut in_pos_f = first.center;
+    let mut out_center_f = first.center;
+    let mut placed = (first, first.center);
+    while in_pos_f <= last_center {
+        let t = out_center_f / sample_rate;
+        let r = positive("pitch_ratio", pitch_ratio(t))?;
+        let s = positive("time_stretch", time_stretch(t))?;
+
+        let grain = &grains[grain_at(&grains, in_pos_f)];
+        let r = if voicing_at(&segments, grain.center as usize) == Some(Voicing::Voiced) {
+            r
+        } else {
+            1.0
+        };
+        let new_period = (grain.period as f32 / r).max(1.0);
+
+        overlap_add(
+            &mut out,
+            &mut overlap_count,
+            grain,
+            &hann(grain.samples.len()),
+            out_center_f,
+        );
+        placed = (grain, out_center_f);
+
+        out_center_f += new_period;
+        in_pos_f += new_period / s;
     }
-    if peak > 1.0 {
-        for s in &mut out {
-            *s /= peak;
+
+    // Likewise the input after the last grain placed, fading out of it.
+    let (grain, at) = placed;
+    head_or_tail(&mut out, &mut overlap_count, input, grain, false, at);
+
+    for (v, &d) in out.iter_mut().zip(&overlap_count) {
+        if d > 1e-6 {
+            *v /= d;
         }
     }
 
-    AudioBuffer {
+    Ok(AudioBuffer {
         sample_rate: input.sample_rate,
         samples: out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grains_land_between_samples() {
+        let samples = [0.0, 0.0, 1.0, 0.0];
+        let grain = Grain {
+            center: 2.5,
+            period: 2,
+            start: 0,
+            samples: &samples,
+        };
+        let (mut out, mut weight) = (Vec::new(), Vec::new());
+        overlap_add(&mut out, &mut weight, &grain, &[1.0; 4], 5.0);
+
+        // The mark moves by 2.5 samples, splitting the impulse in two.
+        assert_eq!(out, [0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.0]);
+        assert_eq!(weight, [0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn grains_keep_fractional_marks() {
+        let buffer = AudioBuffer {
+            sample_rate: 44100,
+            samples: vec![0.0; 100],
+        };
+        let grains = extract_grains(&buffer, &[1.0, 20.4, 50.6, 99.0], &[(0, 10)]);
+        let placed = grains
+            .iter()
+            .map(|g| (g.center, g.start, g.samples.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(placed, [(20.4, 10, 20), (50.6, 41, 20)]);
+    }
+
+    #[test]
+    fn keeps_input_before_and_after_grains() {
+        // A 220 Hz sine starting and ending away from a zero crossing.
+        let input = AudioBuffer {
+            sample_rate: 44100,
+            samples: (0..8820)
+                .map(|i| (1.0 + std::f32::consts::TAU * 220.0 * i as f32 / 44100.0).sin())
+                .collect(),
+        };
+        let analysis = AnalysisOptions::default();
+        let windows = find_window_with(&input, &analysis, None).expect("analysis");
+        let marks = gci_pitch_marks(&input, &windows);
+
+        for ratio in [1.0, 1.5] {
+            let out =
+                psola_curve(&input, &analysis, &marks, |_| ratio, |_| 1.0, None).expect("psola");
+            let (first, last) = (out.samples[0], out.samples[out.len() - 1]);
+            assert!(first.abs() > 0.5, "{ratio}: starts at {first}");
+            assert!(last.abs() > 0.1, "{ratio}: ends at {last}");
+            assert!((out.len() as f32 - 8820.0).abs() < 300.0, "{}", out.len());
+        }
+
+        // Unchanged, the head and tail come back sample for sample.
+        let out = psola_curve(&input, &analysis, &marks, |_| 1.0, |_| 1.0, None).expect("psola");
+        let head = (marks[0].round() as usize).min(out.len());
+        for (i, (a, b)) in out.samples[..head].iter().zip(&input.samples).enumerate() {
+            assert!((a - b).abs() < 1e-4, "sample {i}: {a} != {b}");
+        }
     }
 }
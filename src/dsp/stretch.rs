@@ -5,12 +5,20 @@ use plotters::style::{BLUE, RED};
 use crate::{
     audio::buffer::AudioBuffer,
     dsp::{low_pass, window::hann},
+    error::{Result, VoxlabError, positive},
     plotting::Plot,
 };
 
+/// Frame length of [`time_glide`] in samples.
 const WINDOW_SIZE: usize = 1024;
+/// Distance between analysis frames of [`time_glide`] in samples.
 const ANALYSIS_HOP: usize = 256;
 
+/// Frame length for [`stretch_unvoiced`]. Short, so bursts don't smear much.
+const UNVOICED_FRAME: usize = 512;
+/// Synthesis hop for [`stretch_unvoiced`].
+const UNVOICED_HOP: usize = UNVOICED_FRAME / 4;
+
 /// Naive OLA time-stretch. Robotic artifacts expected (it does not sound human
 /// at all lol).
 #[deprecated = "use PSOLA"]
@@ -52,13 +60,17 @@ pub fn time_stretch(input: &AudioBuffer, stretch: f32) -> AudioBuffer {
 /// Time-stretch where it changes over time. The function takes in a value
 /// between 0 and 1 (where 0 is the first frame and 1 is the last) and outputs
 /// how much to stretch.
-#[deprecated = "use PSOLA"]
+///
+/// # Errors
+/// Returns an error if the stretch isn't positive or plotting fails.
+#[deprecated = "use psola::psola_curve"]
 pub fn time_glide(
     input: &AudioBuffer,
     mut stretch: impl FnMut(f32) -> f32,
     plot: &mut Plot<'_>,
-) -> AudioBuffer {
-    plot.plot(&mut stretch, &BLUE, "raw time glide").unwrap();
+) -> Result<AudioBuffer> {
+    plot.plot(&mut stretch, &BLUE, "raw time glide")
+        .map_err(|err| VoxlabError::Plot(err.to_string()))?;
 
     let window = hann(WINDOW_SIZE);
 
@@ -84,11 +96,10 @@ pub fn time_glide(
         &RED,
         "filtered time glide",
     )
-    .unwrap();
+    .map_err(|err| VoxlabError::Plot(err.to_string()))?;
 
     while (in_pos + WINDOW_SIZE) < input.samples.len() {
-        let stretch = stretch_vals[in_frame];
-        assert!(stretch > 0.0);
+        let stretch = positive("stretch", stretch_vals[in_frame])?;
 
         let synthesis_hop = (ANALYSIS_HOP as f32 * stretch) as usize;
 
@@ -110,8 +121,59 @@ pub fn time_glide(
         in_frame += 1;
     }
 
-    AudioBuffer {
+    Ok(AudioBuffer {
         sample_rate: input.sample_rate,
         samples: output,
+    })
+}
+
+/// OLA time-stretch for unvoiced, noise-like audio (fricatives, aspiration),
+/// which has no pitch marks for PSOLA to work with. Pitch is left untouched.
+/// `time_stretch` takes the position on the output timeline in seconds, like
+/// [`psola_curve`](crate::dsp::psola::psola_curve).
+///
+/// # Errors
+/// Returns an error if `time_stretch` returns a non-positive factor.
+pub fn stretch_unvoiced(
+    input: &AudioBuffer,
+    mut time_stretch: impl FnMut(f32) -> f32,
+) -> Result<AudioBuffer> {
+    if input.samples.len() < UNVOICED_FRAME {
+        return Ok(input.clone());
+    }
+
+    let window = hann(UNVOICED_FRAME);
+    let sample_rate = input.sample_rate as f32;
+
+    let mut out = Vec::new();
+    let mut weight = Vec::new();
+
+    let mut in_pos = 0.0f32;
+    let mut out_pos = 0usize;
+    while in_pos as usize + UNVOICED_FRAME <= input.samples.len() {
+        let s = positive("time_stretch", time_stretch(out_pos as f32 / sample_rate))?;
+
+        out.resize(out_pos + UNVOICED_FRAME, 0.0);
+        weight.resize(out_pos + UNVOICED_FRAME, 0.0);
+
+        let frame = &input.samples[in_pos as usize..in_pos as usize + UNVOICED_FRAME];
+        for (i, (&v, &w)) in frame.iter().zip(&window).enumerate() {
+            out[out_pos + i] += v * w;
+            weight[out_pos + i] += w;
+        }
+
+        out_pos += UNVOICED_HOP;
+        in_pos += UNVOICED_HOP as f32 / s;
     }
+
+    for (v, &w) in out.iter_mut().zip(&weight) {
+        if w > 1e-6 {
+            *v /= w;
+        }
+    }
+
+    Ok(AudioBuffer {
+        sample_rate: input.sample_rate,
+        samples: out,
+    })
 }
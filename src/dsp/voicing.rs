@@ -0,0 +1,342 @@
+//! Voiced/unvoiced segmentation.
+
+use crate::{
+    audio::buffer::AudioBuffer,
+    dsp::window_calc::{AnalysisOptions, PitchFrame, analyse_pitch},
+    error::Result,
+};
+
+/// What a stretch of audio sounds like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Voicing {
+    /// Too quiet to matter.
+    Silence,
+    /// Noise-like: fricatives, aspiration, bursts.
+    Unvoiced,
+    /// Periodic: vowels, sonorants, voiced consonants.
+    Voiced,
+}
+
+/// A stretch of a buffer with a single [`Voicing`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// What the segment sounds like.
+    pub voicing: Voicing,
+    /// First sample of the segment.
+    pub start: usize,
+    /// One past the last sample of the segment.
+    pub end: usize,
+}
+
+impl Segment {
+    /// Length of the segment in samples.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the segment has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Thresholds for [`segment`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoicingOptions {
+    /// Frames with an RMS below this fraction of the loudest frame's are
+    /// silence.
+    pub silence: f32,
+    /// Frames crossing zero more often than this, per sample, are unvoiced.
+    pub max_zero_crossings: f32,
+    /// Frames with a lower pitch confidence than this are unvoiced.
+    pub min_confidence: f32,
+    /// Segments shorter than this, in seconds, are merged into a neighbour.
+    pub min_length: f32,
+}
+
+impl Default for VoicingOptions {
+    /// Silence below 5% of the peak RMS, unvoiced above 0.2 zero crossings
+    /// per sample or below a confidence of 0.6, and segments of at least
+    /// 20 ms.
+    fn default() -> Self {
+        Self {
+            silence: 0.05,
+            max_zero_crossings: 0.2,
+            min_confidence: 0.6,
+            min_length: 0.02,
+        }
+    }
+}
+
+/// Splits `buffer` into silent, unvoiced and voiced segments, classifying
+/// each analysis frame by its energy, zero-crossing rate and pitch
+/// confidence. The segments are contiguous and cover the whole buffer.
+///
+/// # Errors
+/// Returns an error if the analysis options are invalid.
+pub fn segment(
+    buffer: &AudioBuffer,
+    analysis: &AnalysisOptions,
+    options: &VoicingOptions,
+) -> Result<Vec<Segment>> {
+    let frames = analyse_pitch(buffer, analysis, None)?;
+    Ok(segment_frames(buffer, &frames, analysis, options))
+}
+
+/// Like [`segment`], using the result of an earlier
+/// [`analyse_pitch`]`(buffer, analysis, _)` for the pitch confidence.
+pub fn segment_frames(
+    buffer: &AudioBuffer,
+    frames: &[PitchFrame],
+    analysis: &AnalysisOptions,
+    options: &VoicingOptions,
+) -> Vec<Segment> {
+    let len = buffer.len();
+    if len == 0 {
+        return Vec::new();
+    }
+    // Buffers shorter than a frame are classified as a single frame.
+    let window = analysis.window.clamp(1, len);
+    let hop = analysis.hop.max(1);
+
+    let starts = (0..=len - window).step_by(hop).collect::<Vec<_>>();
+    let frame_stats = starts
+        .iter()
+        .map(|&start| {
+            let frame = &buffer.samples[start..start + window];
+            (rms(frame), zero_crossing_rate(frame))
+        })
+        .collect::<Vec<_>>();
+    let loudest = frame_stats.iter().map(|&(rms, _)| rms).fold(0.0, f32::max);
+
+    let mut segments = Vec::<Segment>::new();
+    for (i, (&start, &(rms, zcr))) in starts.iter().zip(&frame_stats).enumerate() {
+        let confidence = frames
+            .binary_search_by_key(&start, |frame| frame.start)
+            .map_or(0.0, |i| frames[i].confidence);
+        let voicing = if rms <= options.silence * loudest {
+            Voicing::Silence
+        } else if zcr <= options.max_zero_crossings && confidence >= options.min_confidence {
+            Voicing::Voiced
+        } else {
+            Voicing::Unvoiced
+        };
+
+        // Each frame stands for the hop around its centre; the first and last
+        // reach the ends of the buffer.
+        let from = if i == 0 {
+            0
+        } else {
+            start + window / 2 - hop / 2
+        };
+        let to = if i + 1 == starts.len() {
+            len
+        } else {
+            start + window / 2 - hop / 2 + hop
+        };
+        match segments.last_mut() {
+            Some(last) if last.voicing == voicing => last.end = to,
+            _ => segments.push(Segment {
+                voicing,
+                start: from,
+                end: to,
+            }),
+        }
+    }
+
+    merge_short(
+        segments,
+        (options.min_length.max(0.0) * buffer.sample_rate as f32) as usize,
+    )
+}
+
+/// Merges segments shorter than `min_len` into the segment before them (or,
+/// for the first one, after them).
+fn merge_short(segments: Vec<Segment>, min_len: usize) -> Vec<Segment> {
+    let mut out = Vec::<Segment>::with_capacity(segments.len());
+    for segment in segments {
+        match out.last_mut() {
+            Some(last) if segment.len() < min_len || last.voicing == segment.voicing => {
+                last.end = segment.end;
+            }
+            _ => out.push(segment),
+        }
+    }
+
+    if out.len() > 1 && out[0].len() < min_len {
+        let first = out.remove(0);
+        out[0].start = first.start;
+    }
+    out
+}
+
+/// Voicing of the segment containing `pos`, or `None` if it's past the end.
+pub fn voicing_at(segments: &[Segment], pos: usize) -> Option<Voicing> {
+    let i = segments.partition_point(|segment| segment.end <= pos);
+    segments
+        .get(i)
+        .filter(|segment| segment.start <= pos)
+        .map(|segment| segment.voicing)
+}
+
+/// Average pitch period, in samples, of the analysis frames centred in voiced
+/// segments, or `None` if there are none.
+pub fn voiced_period(
+    frames: &[PitchFrame],
+    segments: &[Segment],
+    analysis: &AnalysisOptions,
+) -> Option<f32> {
+    let (sum, count) = frames
+        .iter()
+        .filter(|frame| {
+            voicing_at(segments, frame.start + analysis.window / 2) == Some(Voicing::Voiced)
+        })
+        .fold((0.0, 0usize), |(sum, count), frame| {
+            (sum + frame.lag, count + 1)
+        });
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Root mean square of a frame.
+fn rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// Number of sign changes per sample.
+fn zero_crossing_rate(frame: &[f32]) -> f32 {
+    let crossings = frame
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+    crossings as f32 / frame.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::nice::Rng;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// 300 ms of noise, 400 ms of a 200 Hz sine, then 300 ms of noise.
+    fn noise_sine_noise() -> AudioBuffer {
+        let mut rng = Rng::new(7);
+        let samples = (0..44100)
+            .map(|i| {
+                if (13230..30870).contains(&i) {
+                    0.5 * (TAU * 200.0 * i as f32 / SAMPLE_RATE as f32).sin()
+                } else {
+                    rng.next_f32() - 0.5
+                }
+            })
+            .collect();
+        AudioBuffer {
+            sample_rate: SAMPLE_RATE,
+            samples,
+        }
+    }
+
+    #[test]
+    fn splits_sine_from_noise() {
+        let buffer = noise_sine_noise();
+        let segments = segment(
+            &buffer,
+            &AnalysisOptions::default(),
+            &VoicingOptions::default(),
+        )
+        .expect("valid options");
+
+        let voicings = segments.iter().map(|s| s.voicing).collect::<Vec<_>>();
+        assert_eq!(
+            voicings,
+            [Voicing::Unvoiced, Voicing::Voiced, Voicing::Unvoiced],
+            "{segments:?}"
+        );
+        // Contiguous and covering the buffer, with the boundaries within a
+        // frame of the truth.
+        assert_eq!(segments[0].start, 0);
+        assert_eq!(segments[2].end, buffer.len());
+        assert!(segments.windows(2).all(|pair| pair[0].end == pair[1].start));
+        assert!(segments[1].start.abs_diff(13230) < 1024, "{segments:?}");
+        assert!(segments[1].end.abs_diff(30870) < 1024, "{segments:?}");
+
+        assert_eq!(voicing_at(&segments, 22050), Some(Voicing::Voiced));
+        assert_eq!(voicing_at(&segments, 1000), Some(Voicing::Unvoiced));
+        assert_eq!(voicing_at(&segments, buffer.len()), None);
+    }
+
+    #[test]
+    fn voiced_period_of_sine_between_noise() {
+        let buffer = noise_sine_noise();
+        let analysis = AnalysisOptions::default();
+        let frames = analyse_pitch(&buffer, &analysis, None).expect("valid options");
+        let segments = segment_frames(&buffer, &frames, &analysis, &VoicingOptions::default());
+        let period = voiced_period(&frames, &segments, &analysis).expect("voiced frames");
+        // A frame straddling the onset may still count, so allow 2%; the
+        // noise frames alone would throw the average much further.
+        assert!((period - 220.5).abs() < 4.5, "{period}");
+        let all = frames.iter().map(|frame| frame.lag).sum::<f32>() / frames.len() as f32;
+        assert!((all - 220.5).abs() > 20.0, "{all}");
+    }
+
+    #[test]
+    fn voiced_period_ignores_unvoiced_frames() {
+        let analysis = AnalysisOptions {
+            window: 100,
+            hop: 100,
+            ..AnalysisOptions::default()
+        };
+        let segments = [
+            Segment {
+                voicing: Voicing::Unvoiced,
+                start: 0,
+                end: 200,
+            },
+            Segment {
+                voicing: Voicing::Voiced,
+                start: 200,
+                end: 400,
+            },
+            Segment {
+                voicing: Voicing::Silence,
+                start: 400,
+                end: 500,
+            },
+        ];
+        let frames = [
+            (0, 300.0),
+            (100, 300.0),
+            (200, 80.0),
+            (300, 120.0),
+            (400, 500.0),
+        ]
+        .map(|(start, lag)| PitchFrame {
+            start,
+            lag,
+            confidence: 1.0,
+        });
+        assert_eq!(voiced_period(&frames, &segments, &analysis), Some(100.0));
+        assert_eq!(voiced_period(&frames, &segments[..1], &analysis), None);
+    }
+
+    #[test]
+    fn short_segments_are_merged() {
+        let segment = |voicing, start, end| Segment {
+            voicing,
+            start,
+            end,
+        };
+        let merged = merge_short(
+            vec![
+                segment(Voicing::Unvoiced, 0, 10),
+                segment(Voicing::Voiced, 10, 500),
+                segment(Voicing::Unvoiced, 500, 520),
+                segment(Voicing::Voiced, 520, 900),
+            ],
+            100,
+        );
+        assert_eq!(merged, [segment(Voicing::Voiced, 0, 900)]);
+    }
+}
@@ -2,25 +2,30 @@
 
 use plotters::style::{BLACK, full_palette::PURPLE};
 
-use crate::{audio::buffer::AudioBuffer, plotting::Plot};
-
-const ANALYSIS_WINDOW: usize = 1024;
-
-pub fn find_window_single(buffer: &[f32; ANALYSIS_WINDOW], sample_rate: u32) -> usize {
-    let min_lag = sample_rate as usize / 300;
-    let max_lag = (sample_rate as usize / 80).min(ANALYSIS_WINDOW - 1);
+use crate::{
+    audio::buffer::AudioBuffer,
+    dsp::yin::{PitchEstimate, YinOptions, yin},
+    error::{Result, VoxlabError, positive},
+    plotting::Plot,
+};
+
+/// Returns the lag for `min_f0..=max_f0` Hz with the highest autocorrelation
+/// in a single frame.
+pub fn find_window_single(frame: &[f32], sample_rate: u32, min_f0: f32, max_f0: f32) -> usize {
+    let min_lag = ((sample_rate as f32 / max_f0) as usize).max(1);
+    let max_lag = ((sample_rate as f32 / min_f0) as usize).min(frame.len().saturating_sub(1));
 
     let mut best_score = f32::NEG_INFINITY;
     let mut best_lag = min_lag;
 
     for lag in min_lag..=max_lag {
-        let mut score = 0.0f32;
+        let mut score = frame
+            .iter()
+            .zip(&frame[lag..])
+            .map(|(a, b)| a * b)
+            .sum::<f32>();
 
-        for i in 0..(ANALYSIS_WINDOW - lag) {
-            score += buffer[i] * buffer[i + lag];
-        }
-
-        score /= (ANALYSIS_WINDOW - lag) as f32;
+        score /= (frame.len() - lag) as f32;
 
         if score > best_score {
             best_score = score;
@@ -31,38 +36,206 @@ pub fn find_window_single(buffer: &[f32; ANALYSIS_WINDOW], sample_rate: u32) ->
     best_lag
 }
 
-const HOP: usize = ANALYSIS_WINDOW / 4;
+/// Normalised autocorrelation of `frame` at `lag`, clamped to `0.0..=1.0`.
+fn autocorrelation_confidence(frame: &[f32], lag: usize) -> f32 {
+    let energy = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    if energy <= 0.0 || lag >= frame.len() {
+        return 0.0;
+    }
+    let score = frame
+        .iter()
+        .zip(&frame[lag..])
+        .map(|(a, b)| a * b)
+        .sum::<f32>()
+        / (frame.len() - lag) as f32;
+    (score / energy).clamp(0.0, 1.0)
+}
 
-pub fn find_window(buffer: &AudioBuffer, plot: Option<&mut Plot>) -> Vec<(usize, usize)> {
-    let mut results = Vec::new();
-    let mut start = 0usize;
+/// How the pitch period of an analysis frame is estimated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PitchMethod {
+    /// The lag with the highest raw autocorrelation (see
+    /// [`find_window_single`]). Prone to octave errors.
+    Autocorrelation,
+    /// [`yin`] with the given dip threshold (see [`YinOptions::threshold`]).
+    Yin {
+        /// Dip threshold.
+        threshold: f32,
+    },
+}
 
-    while start + ANALYSIS_WINDOW <= buffer.samples.len() {
-        let frame = buffer.samples[start..(start + ANALYSIS_WINDOW)]
-            .as_array()
-            .unwrap();
-        let lag = find_window_single(frame, buffer.sample_rate);
+impl Default for PitchMethod {
+    /// YIN with its default threshold.
+    fn default() -> Self {
+        Self::Yin {
+            threshold: YinOptions::default().threshold,
+        }
+    }
+}
 
-        results.push((start, lag));
+/// How a sample is split into frames and searched for a pitch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnalysisOptions {
+    /// Lowest fundamental frequency searched for, in Hz.
+    pub min_f0: f32,
+    /// Highest fundamental frequency searched for, in Hz.
+    pub max_f0: f32,
+    /// Length of an analysis frame in samples. YIN only finds periods up to
+    /// half of this, so it should be at least two periods of `min_f0`.
+    pub window: usize,
+    /// Distance between analysis frames in samples.
+    pub hop: usize,
+    /// Estimator used on each frame.
+    pub method: PitchMethod,
+}
 
-        start += HOP;
+impl Default for AnalysisOptions {
+    /// 80 Hz to 1 kHz in 1024-sample frames every 256 samples, with YIN.
+    fn default() -> Self {
+        Self {
+            min_f0: 80.0,
+            max_f0: 1000.0,
+            window: 1024,
+            hop: 256,
+            method: PitchMethod::default(),
+        }
     }
+}
 
-    plot.map(|plot| {
+impl AnalysisOptions {
+    /// Checks that the options make sense.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::InvalidParameter`] if a frequency isn't positive,
+    /// the range is empty, or the window or hop is zero.
+    pub fn validate(&self) -> Result<()> {
+        positive("min_f0", self.min_f0)?;
+        positive("max_f0", self.max_f0)?;
+        if self.min_f0 >= self.max_f0 {
+            return Err(VoxlabError::invalid(
+                "max_f0",
+                format!("must be above min_f0 ({})", self.min_f0),
+            ));
+        }
+        if self.window == 0 {
+            return Err(VoxlabError::invalid("window", "must not be zero"));
+        }
+        if self.hop == 0 {
+            return Err(VoxlabError::invalid("hop", "must not be zero"));
+        }
+        Ok(())
+    }
+}
+
+/// Pitch estimate of one analysis frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchFrame {
+    /// Start of the frame in samples.
+    pub start: usize,
+    /// Pitch period in samples.
+    pub lag: f32,
+    /// How periodic the frame is, from 0 (unvoiced) to 1.
+    pub confidence: f32,
+}
+
+/// Estimates the pitch of every analysis frame of `buffer`. Frames no period
+/// in the search range fits in are skipped. Draws the estimates to `plot` if
+/// given.
+///
+/// # Errors
+/// Returns an error if the options are invalid or plotting fails.
+pub fn analyse_pitch(
+    buffer: &AudioBuffer,
+    options: &AnalysisOptions,
+    plot: Option<&mut Plot>,
+) -> Result<Vec<PitchFrame>> {
+    options.validate()?;
+    let mut results = Vec::new();
+
+    for (i, frame) in buffer
+        .samples
+        .windows(options.window)
+        .step_by(options.hop)
+        .enumerate()
+    {
+        let estimate = match options.method {
+            PitchMethod::Autocorrelation => {
+                let lag =
+                    find_window_single(frame, buffer.sample_rate, options.min_f0, options.max_f0);
+                PitchEstimate {
+                    lag: lag as f32,
+                    confidence: autocorrelation_confidence(frame, lag),
+                }
+            }
+            PitchMethod::Yin { threshold } => {
+                let yin_options = YinOptions {
+                    min_f0: options.min_f0,
+                    max_f0: options.max_f0,
+                    threshold,
+                };
+                match yin(frame, buffer.sample_rate, &yin_options) {
+                    Some(estimate) => estimate,
+                    None => continue,
+                }
+            }
+        };
+
+        results.push(PitchFrame {
+            start: i * options.hop,
+            lag: estimate.lag,
+            confidence: estimate.confidence,
+        });
+    }
+
+    if let Some(plot) = plot {
         plot.plot_points(
-            |x| results[(x * results.len() as f32) as usize].0 as f32 / buffer.samples.len() as f32,
+            |x| {
+                results[(x * results.len() as f32) as usize].start as f32
+                    / buffer.samples.len() as f32
+            },
             &PURPLE,
             "Window starts",
         )
-        .unwrap();
+        .map_err(|err| VoxlabError::Plot(err.to_string()))?;
 
         plot.plot_points(
-            |x| results[(x * results.len() as f32) as usize].1 as f32 / ANALYSIS_WINDOW as f32,
+            |x| results[(x * results.len() as f32) as usize].lag / options.window as f32,
             &BLACK,
             "Window lengths",
         )
-        .unwrap();
-    });
+        .map_err(|err| VoxlabError::Plot(err.to_string()))?;
+    }
+
+    Ok(results)
+}
+
+/// Estimates the pitch period of every analysis frame of `buffer` with the
+/// default [`AnalysisOptions`], returning `(frame start, lag)` pairs. Draws
+/// the estimates to `plot` if given.
+///
+/// # Errors
+/// Returns an error if plotting fails.
+pub fn find_window(buffer: &AudioBuffer, plot: Option<&mut Plot>) -> Result<Vec<(usize, usize)>> {
+    find_window_with(buffer, &AnalysisOptions::default(), plot)
+}
+
+/// Like [`find_window`], analysing with `options`.
+///
+/// # Errors
+/// Returns an error if the options are invalid or plotting fails.
+pub fn find_window_with(
+    buffer: &AudioBuffer,
+    options: &AnalysisOptions,
+    plot: Option<&mut Plot>,
+) -> Result<Vec<(usize, usize)>> {
+    Ok(frame_windows(&analyse_pitch(buffer, options, plot)?))
+}
 
-    results
+/// Converts pitch frames to the `(frame start, lag)` pairs [`find_window`]
+/// returns.
+pub fn frame_windows(frames: &[PitchFrame]) -> Vec<(usize, usize)> {
+    frames
+        .iter()
+        .map(|frame| (frame.start, (frame.lag.round() as usize).max(1)))
+        .collect()
 }
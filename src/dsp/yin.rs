@@ -0,0 +1,147 @@
+//! YIN pitch estimation (de Cheveigné & Kawahara, 2002).
+
+/// Options for [`yin`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct YinOptions {
+    /// Lowest fundamental frequency searched for, in Hz.
+    pub min_f0: f32,
+    /// Highest fundamental frequency searched for, in Hz.
+    pub max_f0: f32,
+    /// The first dip of the normalised difference below this is taken as the
+    /// period. Lower is stricter; 0.1 to 0.2 works for most voices.
+    pub threshold: f32,
+}
+
+impl Default for YinOptions {
+    /// 80 Hz to 1 kHz with a threshold of 0.15.
+    fn default() -> Self {
+        Self {
+            min_f0: 80.0,
+            max_f0: 1000.0,
+            threshold: 0.15,
+        }
+    }
+}
+
+/// Pitch estimate of a single frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchEstimate {
+    /// Pitch period in samples, with sub-sample precision.
+    pub lag: f32,
+    /// How periodic the frame is at `lag`, from 0 (noise or silence) to 1
+    /// (perfectly periodic).
+    pub confidence: f32,
+}
+
+/// Estimates the pitch period of `frame` with YIN.
+///
+/// Periods are searched up to half the frame length, so the frame must be at
+/// least two periods of `options.min_f0` long to cover the whole range.
+/// Returns `None` if the frame is too short for any period in the range.
+pub fn yin(frame: &[f32], sample_rate: u32, options: &YinOptions) -> Option<PitchEstimate> {
+    let sample_rate = sample_rate as f32;
+    let min_lag = ((sample_rate / options.max_f0).floor() as usize).max(2);
+    let max_lag = ((sample_rate / options.min_f0).ceil() as usize).min(frame.len() / 2);
+    if min_lag >= max_lag {
+        return None;
+    }
+    let width = frame.len() - max_lag;
+
+    // Cumulative mean normalised difference; d'(0) is 1 by definition.
+    let mut cmnd = vec![1.0f32; max_lag + 1];
+    let mut running = 0.0f32;
+    for (tau, value) in cmnd.iter_mut().enumerate().skip(1) {
+        let diff = frame[..width]
+            .iter()
+            .zip(&frame[tau..tau + width])
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>();
+        running += diff;
+        if running > 0.0 {
+            *value = diff * tau as f32 / running;
+        }
+    }
+
+    // The first dip below the threshold, followed down to its minimum, or the
+    // lowest point overall if there's none.
+    let tau = match (min_lag..=max_lag).find(|&tau| cmnd[tau] < options.threshold) {
+        Some(mut tau) => {
+            while tau < max_lag && cmnd[tau + 1] < cmnd[tau] {
+                tau += 1;
+            }
+            tau
+        }
+        None => (min_lag..=max_lag).min_by(|&a, &b| cmnd[a].total_cmp(&cmnd[b]))?,
+    };
+
+    Some(PitchEstimate {
+        lag: tau as f32 + parabolic_offset(&cmnd, tau),
+        confidence: (1.0 - cmnd[tau]).clamp(0.0, 1.0),
+    })
+}
+
+/// Offset from `i` of the minimum of the parabola through `values[i - 1..=i +
+/// 1]`, in `-0.5..=0.5`. Zero at the ends of `values`.
+fn parabolic_offset(values: &[f32], i: usize) -> f32 {
+    let (Some(&prev), Some(&cur), Some(&next)) = (
+        i.checked_sub(1).and_then(|i| values.get(i)),
+        values.get(i),
+        values.get(i + 1),
+    ) else {
+        return 0.0;
+    };
+
+    let curvature = prev - 2.0 * cur + next;
+    if curvature > 0.0 {
+        (0.5 * (prev - next) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::nice::Rng;
+
+    fn sine(freq: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (TAU * freq * i as f32 / 44100.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn finds_period_of_sine() {
+        for freq in [110.0, 220.0, 440.0] {
+            let estimate = yin(&sine(freq, 2048), 44100, &YinOptions::default()).expect("estimate");
+            assert!(
+                (estimate.lag - 44100.0 / freq).abs() < 0.1,
+                "{freq} Hz: lag {}",
+                estimate.lag
+            );
+            assert!(estimate.confidence > 0.95);
+        }
+    }
+
+    #[test]
+    fn noise_is_not_confident() {
+        let mut rng = Rng::new(1);
+        let noise = (0..2048)
+            .map(|_| 2.0 * rng.next_f32() - 1.0)
+            .collect::<Vec<_>>();
+        let confidence =
+            yin(&noise, 44100, &YinOptions::default()).map_or(0.0, |estimate| estimate.confidence);
+        assert!(confidence < 0.5, "confidence {confidence}");
+    }
+
+    #[test]
+    fn short_frames() {
+        let options = YinOptions::default();
+        assert_eq!(yin(&sine(440.0, 80), 44100, &options), None);
+        // Too short for 80 Hz, but still long enough for 440 Hz.
+        let estimate = yin(&sine(440.0, 512), 44100, &options).expect("estimate");
+        assert!((estimate.lag - 44100.0 / 440.0).abs() < 0.1);
+    }
+}
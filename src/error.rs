@@ -0,0 +1,117 @@
+//! Errors.
+
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{phoneme::ipa::Phoneme, score::midi::MidiError};
+
+/// Result type used throughout voxlab.
+pub type Result<T> = std::result::Result<T, VoxlabError>;
+
+/// Everything that can go wrong while loading voices and rendering.
+#[derive(Debug)]
+pub enum VoxlabError {
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+    /// An audio file is in a format that can't be used.
+    UnsupportedFormat(String),
+    /// A sample has no voiced region to work with.
+    NoVoicedRegion,
+    /// No pitch marks could be placed in a sample, so it can't be analysed or
+    /// pitch-shifted.
+    NoPitchMarks,
+    /// The voice has no sample for a phoneme.
+    MissingSample {
+        /// The phoneme that was needed.
+        phoneme: Phoneme,
+        /// Where the sample was looked for.
+        path: PathBuf,
+    },
+    /// A parameter was out of range.
+    InvalidParameter {
+        /// Name of the parameter.
+        name: &'static str,
+        /// What was wrong with it.
+        reason: String,
+    },
+    /// A voicebank manifest couldn't be understood.
+    Manifest(String),
+    /// A MIDI file couldn't be imported.
+    Midi(MidiError),
+    /// Drawing a debugging plot failed.
+    Plot(String),
+}
+
+impl VoxlabError {
+    /// Shorthand for [`VoxlabError::InvalidParameter`].
+    pub fn invalid(name: &'static str, reason: impl Display) -> Self {
+        Self::InvalidParameter {
+            name,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Checks that a parameter is a positive, finite number.
+pub(crate) fn positive(name: &'static str, value: f32) -> Result<f32> {
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(VoxlabError::invalid(
+            name,
+            format!("must be positive, got {value}"),
+        ))
+    }
+}
+
+impl Display for VoxlabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoxlabError::Io(err) => write!(f, "I/O error: {err}"),
+            VoxlabError::UnsupportedFormat(what) => write!(f, "unsupported audio format: {what}"),
+            VoxlabError::NoVoicedRegion => f.write_str("sample has no voiced region"),
+            VoxlabError::NoPitchMarks => f.write_str("no pitch marks could be found in sample"),
+            VoxlabError::MissingSample { phoneme, path } => write!(
+                f,
+                "no sample for phoneme {phoneme:?} (looked for {})",
+                path.display()
+            ),
+            VoxlabError::InvalidParameter { name, reason } => {
+                write!(f, "invalid parameter `{name}`: {reason}")
+            }
+            VoxlabError::Manifest(reason) => write!(f, "invalid voice manifest: {reason}"),
+            VoxlabError::Midi(err) => Display::fmt(err, f),
+            VoxlabError::Plot(reason) => write!(f, "failed to plot: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for VoxlabError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VoxlabError::Io(err) => Some(err),
+            VoxlabError::Midi(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for VoxlabError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<hound::Error> for VoxlabError {
+    fn from(value: hound::Error) -> Self {
+        match value {
+            hound::Error::IoError(err) => Self::Io(err),
+            other => Self::UnsupportedFormat(other.to_string()),
+        }
+    }
+}
+
+impl From<MidiError> for VoxlabError {
+    fn from(value: MidiError) -> Self {
+        Self::Midi(value)
+    }
+}
@@ -0,0 +1,20 @@
+//! Voxlab, a concatenative singing synthesiser.
+//!
+//! A score is a list of [`scheduling::PhonemeInstance`]s, which is
+//! [scheduled](Schedule) into a [`GrainTimeline`] and rendered with samples
+//! from a [`Voice`] into an [`AudioBuffer`].
+
+pub mod audio;
+pub mod dsp;
+pub mod error;
+mod nice;
+pub mod phoneme;
+pub mod plotting;
+pub mod samples;
+pub mod scheduling;
+pub mod score;
+
+pub use audio::buffer::AudioBuffer;
+pub use error::{Result, VoxlabError};
+pub use samples::Voice;
+pub use scheduling::{GrainTimeline, Schedule};
@@ -1,41 +1,358 @@
 //! Entrypoint for voxlab.
 
-mod audio;
-mod dsp;
-mod nice;
-mod phoneme;
-mod plotting;
-mod samples;
-mod scheduling;
-
-use std::{collections::HashMap, error::Error};
-
-use crate::{
-    audio::{MidiNote, wav::export_wav},
-    phoneme::ipa::{Phoneme, Vowel},
-    samples::Voice,
-    scheduling::{InstanceId, PhonemeInstance, PhonemeOptions, Schedule as _, TransitionOptions},
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use voxlab::{
+    AudioBuffer, GrainTimeline, Schedule as _, Voice,
+    audio::{
+        MidiNote,
+        buffer::Downmix,
+        wav::{Dither, ExportOptions, export_wav_with, import_wav_with},
+    },
+    dsp::{
+        gci::gci_pitch_marks,
+        voicing::{VoicingOptions, segment_frames, voiced_period},
+        window_calc::{AnalysisOptions, analyse_pitch, frame_windows},
+    },
+    phoneme::ipa::Phoneme,
+    scheduling::{
+        TransitionOptions,
+        dynamics::AdsrOptions,
+        portamento::{PortamentoOptions, PortamentoShape},
+        vibrato::VibratoOptions,
+    },
+    score::{
+        midi::{MidiImportOptions, import_midi},
+        note::LoweringOptions,
+    },
 };
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let phonemes = (50..=60usize)
-        .map(|i| PhonemeInstance {
-            instance_id: InstanceId::new(i),
-            steady_grains: 20,
-            phoneme: Phoneme::Vowel(Vowel::CloseFrontUnrounded),
-            length: 1.0,
-            options: PhonemeOptions {
-                next_transition: Some(TransitionOptions { length_grains: 10 }),
+/// Sample rate voicebanks are loaded at unless told otherwise, in Hz.
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
+/// Concatenative singing synthesiser.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// What to do.
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Subcommands.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Render a score to a WAV file.
+    Render {
+        /// How to read the score.
+        #[command(flatten)]
+        score: ScoreArgs,
+        /// Voicebank directory.
+        #[arg(long)]
+        voice: PathBuf,
+        /// Output WAV file.
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Sample rate of the output in Hz.
+        #[arg(long, default_value_t = DEFAULT_SAMPLE_RATE)]
+        sample_rate: u32,
+        /// Sample format of the output.
+        #[arg(long, value_enum, default_value_t = Format::F32)]
+        format: Format,
+        /// Number of channels in the output.
+        #[arg(long, default_value_t = 1)]
+        channels: u16,
+        /// Dither used when writing integer samples.
+        #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+        dither: DitherArg,
+    },
+    /// Print the pitch analysis of a sample.
+    Analyze {
+        /// WAV file.
+        wav: PathBuf,
+        /// Analyse only this channel of a multichannel file instead of the
+        /// average of all channels.
+        #[arg(long)]
+        channel: Option<usize>,
+        /// Lowest fundamental frequency searched for, in Hz.
+        #[arg(long, default_value_t = AnalysisOptions::default().min_f0)]
+        min_f0: f32,
+        /// Highest fundamental frequency searched for, in Hz.
+        #[arg(long, default_value_t = AnalysisOptions::default().max_f0)]
+        max_f0: f32,
+        /// Length of an analysis frame in samples.
+        #[arg(long, default_value_t = AnalysisOptions::default().window)]
+        window: usize,
+        /// Distance between analysis frames in samples.
+        #[arg(long, default_value_t = AnalysisOptions::default().hop)]
+        hop: usize,
+    },
+    /// Print the grain timeline a score is scheduled into.
+    InspectTimeline {
+        /// How to read the score.
+        #[command(flatten)]
+        score: ScoreArgs,
+        /// Voicebank directory to take preutterance and overlap from.
+        #[arg(long)]
+        voice: Option<PathBuf>,
+    },
+}
+
+/// Sample formats of rendered files.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    /// 16-bit integer.
+    I16,
+    /// 24-bit integer.
+    I24,
+    /// 32-bit integer.
+    I32,
+    /// 32-bit float.
+    F32,
+}
+
+/// Dither of rendered files.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DitherArg {
+    /// Plain rounding.
+    None,
+    /// Triangular noise.
+    Tpdf,
+    /// Noise-shaped triangular noise.
+    Shaped,
+}
+
+/// Shapes of pitch glides.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ShapeArg {
+    /// Constant rate.
+    Linear,
+    /// S-curve.
+    Sigmoid,
+    /// S-curve going 15% past the target.
+    Overshoot,
+    /// S-curve first moving 15% away from the target.
+    Preparation,
+}
+
+/// Arguments shared by commands that read a score.
+#[derive(Debug, clap::Args)]
+struct ScoreArgs {
+    /// Standard MIDI file.
+    score: PathBuf,
+    /// Only import notes from this track.
+    #[arg(long)]
+    track: Option<usize>,
+    /// IPA symbol sung on notes without a lyric.
+    #[arg(long, default_value = "ɑ", value_parser = parse_phoneme)]
+    phoneme: Phoneme,
+    /// Length of the transition between consecutive notes, in pitch periods.
+    #[arg(long)]
+    transition: Option<usize>,
+    /// Glide between the pitches of consecutive notes over this many seconds.
+    /// Needs `--transition`.
+    #[arg(long, requires = "transition")]
+    portamento: Option<f32>,
+    /// How the pitch glides between notes.
+    #[arg(long, value_enum, default_value_t = ShapeArg::Sigmoid, requires = "portamento")]
+    portamento_shape: ShapeArg,
+    /// Sing every note with vibrato of this depth, in cents.
+    #[arg(long)]
+    vibrato_depth: Option<f32>,
+    /// Rate of the vibrato in Hz.
+    #[arg(long, default_value_t = VibratoOptions::default().rate, requires = "vibrato_depth")]
+    vibrato_rate: f32,
+    /// Length of each consonant of a lyric, in seconds.
+    #[arg(long, default_value_t = LoweringOptions::default().consonant_length)]
+    consonant_length: f64,
+    /// Shape the loudness of every note with an attack, decay, sustain and
+    /// release envelope.
+    #[arg(long)]
+    envelope: bool,
+    /// Pitch offset of a fully deflected pitch wheel, in cents.
+    #[arg(long, default_value_t = MidiImportOptions::default().bend_range)]
+    bend_range: f32,
+}
+
+impl ScoreArgs {
+    /// Imports and schedules the score, with the preutterance and overlap of
+    /// `voice` if given.
+    fn timeline(&self, voice: Option<&mut Voice>) -> voxlab::Result<GrainTimeline> {
+        let score = import_midi(
+            &self.score,
+            &MidiImportOptions {
+                track: self.track,
+                default_phoneme: self.phoneme,
+                transition: self.transition.map(|length_grains| TransitionOptions {
+                    length_grains,
+                    portamento: self.portamento.map(|length| PortamentoOptions {
+                        length,
+                        shape: match self.portamento_shape {
+                            ShapeArg::Linear => PortamentoShape::Linear,
+                            ShapeArg::Sigmoid => PortamentoShape::Sigmoid,
+                            ShapeArg::Overshoot => PortamentoShape::Overshoot { amount: 0.15 },
+                            ShapeArg::Preparation => PortamentoShape::Preparation { amount: 0.15 },
+                        },
+                    }),
+                }),
+                vibrato: self.vibrato_depth.map(|depth| VibratoOptions {
+                    depth,
+                    rate: self.vibrato_rate,
+                    ..VibratoOptions::default()
+                }),
+                bend_range: self.bend_range,
+                envelope: self.envelope.then(AdsrOptions::default),
+                lowering: LoweringOptions {
+                    consonant_length: self.consonant_length,
+                    ..LoweringOptions::default()
+                },
             },
-            note: MidiNote(i as f32),
-        })
-        .collect::<Vec<_>>();
+        )?;
+        let mut timeline = match voice {
+            Some(voice) => score.instances.schedule_for(&score.tempo, voice)?,
+            None => score.instances.schedule_with(&score.tempo),
+        };
+        timeline.pitch_bend = score.pitch_bend.resolve(&score.tempo);
+        timeline.dynamics = score.dynamics.resolve(&score.tempo);
+        Ok(timeline)
+    }
+}
+
+/// Parses a phoneme argument.
+fn parse_phoneme(symbol: &str) -> Result<Phoneme, String> {
+    Phoneme::from_ipa(symbol).ok_or_else(|| format!("unknown IPA symbol {symbol:?}"))
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let scheduled = dbg!(phonemes.schedule());
+/// Runs a command.
+fn run(cli: Cli) -> voxlab::Result<()> {
+    match cli.command {
+        Command::Render {
+            score,
+            voice,
+            output,
+            sample_rate,
+            format,
+            channels,
+            dither,
+        } => {
+            let mut voice = Voice::load(voice, sample_rate)?;
+            let timeline = score.timeline(Some(&mut voice))?;
+            let rendered = timeline.render(&mut voice)?;
+            let options = ExportOptions {
+                channels,
+                dither: match dither {
+                    DitherArg::None => Dither::None,
+                    DitherArg::Tpdf => Dither::Tpdf,
+                    DitherArg::Shaped => Dither::Shaped,
+                },
+                ..match format {
+                    Format::I16 => ExportOptions::pcm(16),
+                    Format::I24 => ExportOptions::pcm(24),
+                    Format::I32 => ExportOptions::pcm(32),
+                    Format::F32 => ExportOptions::default(),
+                }
+            };
+            let stats = export_wav_with(&rendered.into(), output, &options)?;
+            if stats.clipped > 0 {
+                eprintln!(
+                    "warning: {} samples ({:.3}%) clipped, peak {:.3}",
+                    stats.clipped,
+                    stats.clipped_fraction() * 100.0,
+                    stats.peak
+                );
+            }
+            Ok(())
+        }
+        Command::Analyze {
+            wav,
+            channel,
+            min_f0,
+            max_f0,
+            window,
+            hop,
+        } => analyze(
+            &import_wav_with(wav, channel.map_or(Downmix::Average, Downmix::Channel))?,
+            &AnalysisOptions {
+                min_f0,
+                max_f0,
+                window,
+                hop,
+                ..AnalysisOptions::default()
+            },
+        ),
+        Command::InspectTimeline { score, voice } => {
+            let mut voice = voice
+                .map(|voice| Voice::load(voice, DEFAULT_SAMPLE_RATE))
+                .transpose()?;
+            for event in score.timeline(voice.as_mut())?.events {
+                print!(
+                    "{:<6} {:<8} {:>10.4}s {:>10.4}s {:>7.2}",
+                    event.instance_id.to_string(),
+                    format!("{:?}", event.source),
+                    event.start,
+                    event.duration,
+                    event.note.0
+                );
+                if event.overlap > 0.0 {
+                    print!(" overlap {:.4}s", event.overlap);
+                }
+                match event.interp {
+                    Some(interp) => println!(" -> {} grains", interp.fade_len),
+                    None => println!(),
+                }
+            }
+            Ok(())
+        }
+    }
+}
 
-    let rendered = scheduled.render(&mut Voice::new("samples", 44100, HashMap::new()))?;
+/// Prints the voicing segments, average period, base note and pitch marks of
+/// a sample.
+fn analyze(sample: &AudioBuffer, options: &AnalysisOptions) -> voxlab::Result<()> {
+    let sample_rate = sample.sample_rate as f32;
+    let secs = |samples: usize| samples as f32 / sample_rate;
 
-    export_wav(rendered, "outputs/5d/output.wav")?;
+    let frames = analyse_pitch(sample, options, None)?;
+    let segments = segment_frames(sample, &frames, options, &VoicingOptions::default());
+    let period = voiced_period(&frames, &segments, options);
+    let marks = gci_pitch_marks(sample, &frame_windows(&frames));
 
+    println!("sample rate:   {} Hz", sample.sample_rate);
+    println!("length:        {:.4}s", secs(sample.len()));
+    println!("segments:      {}", segments.len());
+    for segment in &segments {
+        println!(
+            "  {:>8.4}s - {:>8.4}s {:?}",
+            secs(segment.start),
+            secs(segment.end),
+            segment.voicing
+        );
+    }
+    match period {
+        Some(period) => {
+            let note = MidiNote::from_frequency(sample_rate / period);
+            println!(
+                "period:        {period:.2} samples ({:.2} Hz)",
+                sample_rate / period
+            );
+            println!("base note:     {:.2}", note.0);
+        }
+        None => println!("period:        none (no voiced frames)"),
+    }
+    println!("pitch marks:   {}", marks.len());
+    for mark in marks {
+        println!("  {mark:>10.2} {:>10.4}s", mark / sample_rate);
+    }
     Ok(())
 }
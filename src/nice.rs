@@ -4,3 +4,36 @@
 pub fn lerp(x: f32, y: f32, t: f32) -> f32 {
     ((1.0 - t) * x) + (t * y)
 }
+
+/// Smooth S-curve from 0 to 1 for `t` in `0..=1`.
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Small, fast, deterministic pseudo-random number generator (xorshift64*).
+/// Not for anything that needs to be unpredictable.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from `seed`. Any seed works, including zero.
+    pub fn new(seed: u64) -> Self {
+        // Xorshift gets stuck at zero, so mix the seed into a non-zero state.
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15 | 1)
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniformly distributed number in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fill the mantissa exactly.
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
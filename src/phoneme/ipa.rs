@@ -5,8 +5,11 @@ use std::fmt::Debug;
 /// IPA phoneme identifiers.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Phoneme {
+    /// A vowel.
     Vowel(Vowel),
+    /// A consonant.
     Consonant(Consonant),
+    /// Silence between words.
     Space,
 }
 
@@ -19,6 +22,30 @@ impl Phoneme {
             Phoneme::Space => " ",
         }
     }
+
+    /// Looks up the phoneme whose canonical IPA symbol is exactly `symbol`.
+    pub fn from_ipa(symbol: &str) -> Option<Phoneme> {
+        if symbol == " " {
+            return Some(Phoneme::Space);
+        }
+
+        Vowel::ALL
+            .iter()
+            .map(|&v| Phoneme::Vowel(v))
+            .chain(Consonant::ALL.iter().map(|&c| Phoneme::Consonant(c)))
+            .find(|p| p.ipa() == symbol)
+    }
+
+    /// Whether the phoneme is periodic, and so has pitch marks and can be
+    /// pitch-shifted. Vowels and voiced consonants are; voiceless consonants
+    /// and silence aren't.
+    pub fn is_periodic(self) -> bool {
+        match self {
+            Phoneme::Vowel(_) => true,
+            Phoneme::Consonant(c) => c.is_voiced(),
+            Phoneme::Space => false,
+        }
+    }
 }
 
 impl Debug for Phoneme {
@@ -32,32 +59,756 @@ impl Debug for Phoneme {
     }
 }
 
-/// IPA vowel inventory (subset, expandable).
+/// IPA vowel inventory, covering the full IPA vowel chart plus the rhotic
+/// (r-colored) central vowels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Vowel {
-    /// Open back unrounded vowel (IPA: ɑ)
-    OpenBackUnrounded,
     /// Close front unrounded vowel (IPA: i)
     CloseFrontUnrounded,
+    /// Close front rounded vowel (IPA: y)
+    CloseFrontRounded,
+    /// Close central unrounded vowel (IPA: ɨ)
+    CloseCentralUnrounded,
+    /// Close central rounded vowel (IPA: ʉ)
+    CloseCentralRounded,
+    /// Close back unrounded vowel (IPA: ɯ)
+    CloseBackUnrounded,
+    /// Close back rounded vowel (IPA: u)
+    CloseBackRounded,
+    /// Near-close near-front unrounded vowel (IPA: ɪ)
+    NearCloseNearFrontUnrounded,
+    /// Near-close near-front rounded vowel (IPA: ʏ)
+    NearCloseNearFrontRounded,
+    /// Near-close near-back rounded vowel (IPA: ʊ)
+    NearCloseNearBackRounded,
+    /// Close-mid front unrounded vowel (IPA: e)
+    CloseMidFrontUnrounded,
+    /// Close-mid front rounded vowel (IPA: ø)
+    CloseMidFrontRounded,
+    /// Close-mid central unrounded vowel (IPA: ɘ)
+    CloseMidCentralUnrounded,
+    /// Close-mid central rounded vowel (IPA: ɵ)
+    CloseMidCentralRounded,
+    /// Close-mid back unrounded vowel (IPA: ɤ)
+    CloseMidBackUnrounded,
+    /// Close-mid back rounded vowel (IPA: o)
+    CloseMidBackRounded,
+    /// Mid central vowel, schwa (IPA: ə)
+    MidCentral,
+    /// R-colored mid central vowel (IPA: ɚ)
+    RhoticMidCentral,
+    /// Open-mid front unrounded vowel (IPA: ɛ)
+    OpenMidFrontUnrounded,
+    /// Open-mid front rounded vowel (IPA: œ)
+    OpenMidFrontRounded,
+    /// Open-mid central unrounded vowel (IPA: ɜ)
+    OpenMidCentralUnrounded,
+    /// Open-mid central rounded vowel (IPA: ɞ)
+    OpenMidCentralRounded,
+    /// R-colored open-mid central unrounded vowel (IPA: ɝ)
+    RhoticOpenMidCentral,
+    /// Open-mid back unrounded vowel (IPA: ʌ)
+    OpenMidBackUnrounded,
+    /// Open-mid back rounded vowel (IPA: ɔ)
+    OpenMidBackRounded,
+    /// Near-open front unrounded vowel (IPA: æ)
+    NearOpenFrontUnrounded,
+    /// Near-open central vowel (IPA: ɐ)
+    NearOpenCentral,
+    /// Open front unrounded vowel (IPA: a)
+    OpenFrontUnrounded,
+    /// Open front rounded vowel (IPA: ɶ)
+    OpenFrontRounded,
+    /// Open central unrounded vowel (IPA: ä)
+    OpenCentralUnrounded,
+    /// Open back unrounded vowel (IPA: ɑ)
+    OpenBackUnrounded,
+    /// Open back rounded vowel (IPA: ɒ)
+    OpenBackRounded,
+}
+
+/// Vowel height (openness of the jaw), from close to open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Height {
+    /// Close (high) vowels, e.g. i, u.
+    Close,
+    /// Near-close vowels, e.g. ɪ, ʊ.
+    NearClose,
+    /// Close-mid vowels, e.g. e, o.
+    CloseMid,
+    /// Mid vowels, e.g. ə.
+    Mid,
+    /// Open-mid vowels, e.g. ɛ, ɔ.
+    OpenMid,
+    /// Near-open vowels, e.g. æ.
+    NearOpen,
+    /// Open (low) vowels, e.g. a, ɑ.
+    Open,
+}
+
+impl Height {
+    /// Openness from 0 (close) to 1 (open).
+    pub fn openness(self) -> f32 {
+        f32::from(self as u8) / f32::from(Height::Open as u8)
+    }
+}
+
+/// Vowel backness (tongue position), from front to back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Backness {
+    /// Front vowels, e.g. i, e.
+    Front,
+    /// Near-front vowels, e.g. ɪ.
+    NearFront,
+    /// Central vowels, e.g. ə.
+    Central,
+    /// Near-back vowels, e.g. ʊ.
+    NearBack,
+    /// Back vowels, e.g. u, ɑ.
+    Back,
+}
+
+impl Backness {
+    /// Backness from 0 (front) to 1 (back).
+    pub fn position(self) -> f32 {
+        f32::from(self as u8) / f32::from(Backness::Back as u8)
+    }
 }
 
 impl Vowel {
+    /// Every vowel in the inventory.
+    pub const ALL: &[Vowel] = &[
+        Vowel::CloseFrontUnrounded,
+        Vowel::CloseFrontRounded,
+        Vowel::CloseCentralUnrounded,
+        Vowel::CloseCentralRounded,
+        Vowel::CloseBackUnrounded,
+        Vowel::CloseBackRounded,
+        Vowel::NearCloseNearFrontUnrounded,
+        Vowel::NearCloseNearFrontRounded,
+        Vowel::NearCloseNearBackRounded,
+        Vowel::CloseMidFrontUnrounded,
+        Vowel::CloseMidFrontRounded,
+        Vowel::CloseMidCentralUnrounded,
+        Vowel::CloseMidCentralRounded,
+        Vowel::CloseMidBackUnrounded,
+        Vowel::CloseMidBackRounded,
+        Vowel::MidCentral,
+        Vowel::RhoticMidCentral,
+        Vowel::OpenMidFrontUnrounded,
+        Vowel::OpenMidFrontRounded,
+        Vowel::OpenMidCentralUnrounded,
+        Vowel::OpenMidCentralRounded,
+        Vowel::RhoticOpenMidCentral,
+        Vowel::OpenMidBackUnrounded,
+        Vowel::OpenMidBackRounded,
+        Vowel::NearOpenFrontUnrounded,
+        Vowel::NearOpenCentral,
+        Vowel::OpenFrontUnrounded,
+        Vowel::OpenFrontRounded,
+        Vowel::OpenCentralUnrounded,
+        Vowel::OpenBackUnrounded,
+        Vowel::OpenBackRounded,
+    ];
+
     /// Canonical IPA symbol.
     pub fn ipa(&self) -> &'static str {
         match self {
-            Vowel::OpenBackUnrounded => "ɑ",
             Vowel::CloseFrontUnrounded => "i",
+            Vowel::CloseFrontRounded => "y",
+            Vowel::CloseCentralUnrounded => "ɨ",
+            Vowel::CloseCentralRounded => "ʉ",
+            Vowel::CloseBackUnrounded => "ɯ",
+            Vowel::CloseBackRounded => "u",
+            Vowel::NearCloseNearFrontUnrounded => "ɪ",
+            Vowel::NearCloseNearFrontRounded => "ʏ",
+            Vowel::NearCloseNearBackRounded => "ʊ",
+            Vowel::CloseMidFrontUnrounded => "e",
+            Vowel::CloseMidFrontRounded => "ø",
+            Vowel::CloseMidCentralUnrounded => "ɘ",
+            Vowel::CloseMidCentralRounded => "ɵ",
+            Vowel::CloseMidBackUnrounded => "ɤ",
+            Vowel::CloseMidBackRounded => "o",
+            Vowel::MidCentral => "ə",
+            Vowel::RhoticMidCentral => "ɚ",
+            Vowel::OpenMidFrontUnrounded => "ɛ",
+            Vowel::OpenMidFrontRounded => "œ",
+            Vowel::OpenMidCentralUnrounded => "ɜ",
+            Vowel::OpenMidCentralRounded => "ɞ",
+            Vowel::RhoticOpenMidCentral => "ɝ",
+            Vowel::OpenMidBackUnrounded => "ʌ",
+            Vowel::OpenMidBackRounded => "ɔ",
+            Vowel::NearOpenFrontUnrounded => "æ",
+            Vowel::NearOpenCentral => "ɐ",
+            Vowel::OpenFrontUnrounded => "a",
+            Vowel::OpenFrontRounded => "ɶ",
+            Vowel::OpenCentralUnrounded => "ä",
+            Vowel::OpenBackUnrounded => "ɑ",
+            Vowel::OpenBackRounded => "ɒ",
         }
     }
+
+    /// Height, backness and roundedness.
+    fn features(self) -> (Height, Backness, bool) {
+        match self {
+            Vowel::CloseFrontUnrounded => (Height::Close, Backness::Front, false),
+            Vowel::CloseFrontRounded => (Height::Close, Backness::Front, true),
+            Vowel::CloseCentralUnrounded => (Height::Close, Backness::Central, false),
+            Vowel::CloseCentralRounded => (Height::Close, Backness::Central, true),
+            Vowel::CloseBackUnrounded => (Height::Close, Backness::Back, false),
+            Vowel::CloseBackRounded => (Height::Close, Backness::Back, true),
+            Vowel::NearCloseNearFrontUnrounded => (Height::NearClose, Backness::NearFront, false),
+            Vowel::NearCloseNearFrontRounded => (Height::NearClose, Backness::NearFront, true),
+            Vowel::NearCloseNearBackRounded => (Height::NearClose, Backness::NearBack, true),
+            Vowel::CloseMidFrontUnrounded => (Height::CloseMid, Backness::Front, false),
+            Vowel::CloseMidFrontRounded => (Height::CloseMid, Backness::Front, true),
+            Vowel::CloseMidCentralUnrounded => (Height::CloseMid, Backness::Central, false),
+            Vowel::CloseMidCentralRounded => (Height::CloseMid, Backness::Central, true),
+            Vowel::CloseMidBackUnrounded => (Height::CloseMid, Backness::Back, false),
+            Vowel::CloseMidBackRounded => (Height::CloseMid, Backness::Back, true),
+            Vowel::MidCentral | Vowel::RhoticMidCentral => (Height::Mid, Backness::Central, false),
+            Vowel::OpenMidFrontUnrounded => (Height::OpenMid, Backness::Front, false),
+            Vowel::OpenMidFrontRounded => (Height::OpenMid, Backness::Front, true),
+            Vowel::OpenMidCentralUnrounded | Vowel::RhoticOpenMidCentral => {
+                (Height::OpenMid, Backness::Central, false)
+            }
+            Vowel::OpenMidCentralRounded => (Height::OpenMid, Backness::Central, true),
+            Vowel::OpenMidBackUnrounded => (Height::OpenMid, Backness::Back, false),
+            Vowel::OpenMidBackRounded => (Height::OpenMid, Backness::Back, true),
+            Vowel::NearOpenFrontUnrounded => (Height::NearOpen, Backness::Front, false),
+            Vowel::NearOpenCentral => (Height::NearOpen, Backness::Central, false),
+            Vowel::OpenFrontUnrounded => (Height::Open, Backness::Front, false),
+            Vowel::OpenFrontRounded => (Height::Open, Backness::Front, true),
+            Vowel::OpenCentralUnrounded => (Height::Open, Backness::Central, false),
+            Vowel::OpenBackUnrounded => (Height::Open, Backness::Back, false),
+            Vowel::OpenBackRounded => (Height::Open, Backness::Back, true),
+        }
+    }
+
+    /// How open the vowel is.
+    pub fn height(self) -> Height {
+        self.features().0
+    }
+
+    /// How far back the vowel is articulated.
+    pub fn backness(self) -> Backness {
+        self.features().1
+    }
+
+    /// Whether the lips are rounded.
+    pub fn is_rounded(self) -> bool {
+        self.features().2
+    }
+
+    /// Whether the vowel is r-colored.
+    pub fn is_rhotic(self) -> bool {
+        matches!(self, Vowel::RhoticMidCentral | Vowel::RhoticOpenMidCentral)
+    }
+
+    /// Articulatory distance to `other`: 0 for identical vowels, around 1 for
+    /// opposite corners of the vowel chart. Rounding and rhoticity each count
+    /// for half a chart side.
+    pub fn distance(self, other: Vowel) -> f32 {
+        let height = self.height().openness() - other.height().openness();
+        let backness = self.backness().position() - other.backness().position();
+        let rounding = if self.is_rounded() == other.is_rounded() {
+            0.0
+        } else {
+            0.5
+        };
+        let rhotic = if self.is_rhotic() == other.is_rhotic() {
+            0.0
+        } else {
+            0.5
+        };
+
+        (height * height + backness * backness + rounding * rounding + rhotic * rhotic).sqrt()
+            / 2.0f32.sqrt()
+    }
 }
 
-/// IPA consonant inventory (placeholder).
+/// IPA consonant inventory: the pulmonic consonant chart plus the common
+/// affricates.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Consonant {}
+pub enum Consonant {
+    /// Voiceless bilabial plosive (IPA: p)
+    VoicelessBilabialPlosive,
+    /// Voiced bilabial plosive (IPA: b)
+    VoicedBilabialPlosive,
+    /// Voiceless alveolar plosive (IPA: t)
+    VoicelessAlveolarPlosive,
+    /// Voiced alveolar plosive (IPA: d)
+    VoicedAlveolarPlosive,
+    /// Voiceless retroflex plosive (IPA: ʈ)
+    VoicelessRetroflexPlosive,
+    /// Voiced retroflex plosive (IPA: ɖ)
+    VoicedRetroflexPlosive,
+    /// Voiceless palatal plosive (IPA: c)
+    VoicelessPalatalPlosive,
+    /// Voiced palatal plosive (IPA: ɟ)
+    VoicedPalatalPlosive,
+    /// Voiceless velar plosive (IPA: k)
+    VoicelessVelarPlosive,
+    /// Voiced velar plosive (IPA: ɡ)
+    VoicedVelarPlosive,
+    /// Voiceless uvular plosive (IPA: q)
+    VoicelessUvularPlosive,
+    /// Voiced uvular plosive (IPA: ɢ)
+    VoicedUvularPlosive,
+    /// Glottal stop (IPA: ʔ)
+    GlottalStop,
+    /// Bilabial nasal (IPA: m)
+    BilabialNasal,
+    /// Labiodental nasal (IPA: ɱ)
+    LabiodentalNasal,
+    /// Alveolar nasal (IPA: n)
+    AlveolarNasal,
+    /// Retroflex nasal (IPA: ɳ)
+    RetroflexNasal,
+    /// Palatal nasal (IPA: ɲ)
+    PalatalNasal,
+    /// Velar nasal (IPA: ŋ)
+    VelarNasal,
+    /// Uvular nasal (IPA: ɴ)
+    UvularNasal,
+    /// Bilabial trill (IPA: ʙ)
+    BilabialTrill,
+    /// Alveolar trill (IPA: r)
+    AlveolarTrill,
+    /// Uvular trill (IPA: ʀ)
+    UvularTrill,
+    /// Labiodental flap (IPA: ⱱ)
+    LabiodentalFlap,
+    /// Alveolar tap (IPA: ɾ)
+    AlveolarTap,
+    /// Retroflex flap (IPA: ɽ)
+    RetroflexFlap,
+    /// Voiceless bilabial fricative (IPA: ɸ)
+    VoicelessBilabialFricative,
+    /// Voiced bilabial fricative (IPA: β)
+    VoicedBilabialFricative,
+    /// Voiceless labiodental fricative (IPA: f)
+    VoicelessLabiodentalFricative,
+    /// Voiced labiodental fricative (IPA: v)
+    VoicedLabiodentalFricative,
+    /// Voiceless dental fricative (IPA: θ)
+    VoicelessDentalFricative,
+    /// Voiced dental fricative (IPA: ð)
+    VoicedDentalFricative,
+    /// Voiceless alveolar fricative (IPA: s)
+    VoicelessAlveolarFricative,
+    /// Voiced alveolar fricative (IPA: z)
+    VoicedAlveolarFricative,
+    /// Voiceless postalveolar fricative (IPA: ʃ)
+    VoicelessPostalveolarFricative,
+    /// Voiced postalveolar fricative (IPA: ʒ)
+    VoicedPostalveolarFricative,
+    /// Voiceless retroflex fricative (IPA: ʂ)
+    VoicelessRetroflexFricative,
+    /// Voiced retroflex fricative (IPA: ʐ)
+    VoicedRetroflexFricative,
+    /// Voiceless alveolo-palatal fricative (IPA: ɕ)
+    VoicelessAlveoloPalatalFricative,
+    /// Voiced alveolo-palatal fricative (IPA: ʑ)
+    VoicedAlveoloPalatalFricative,
+    /// Voiceless palatal fricative (IPA: ç)
+    VoicelessPalatalFricative,
+    /// Voiced palatal fricative (IPA: ʝ)
+    VoicedPalatalFricative,
+    /// Voiceless velar fricative (IPA: x)
+    VoicelessVelarFricative,
+    /// Voiced velar fricative (IPA: ɣ)
+    VoicedVelarFricative,
+    /// Voiceless uvular fricative (IPA: χ)
+    VoicelessUvularFricative,
+    /// Voiced uvular fricative (IPA: ʁ)
+    VoicedUvularFricative,
+    /// Voiceless pharyngeal fricative (IPA: ħ)
+    VoicelessPharyngealFricative,
+    /// Voiced pharyngeal fricative (IPA: ʕ)
+    VoicedPharyngealFricative,
+    /// Voiceless glottal fricative (IPA: h)
+    VoicelessGlottalFricative,
+    /// Voiced glottal fricative (IPA: ɦ)
+    VoicedGlottalFricative,
+    /// Voiceless alveolar lateral fricative (IPA: ɬ)
+    VoicelessAlveolarLateralFricative,
+    /// Voiced alveolar lateral fricative (IPA: ɮ)
+    VoicedAlveolarLateralFricative,
+    /// Labiodental approximant (IPA: ʋ)
+    LabiodentalApproximant,
+    /// Alveolar approximant (IPA: ɹ)
+    AlveolarApproximant,
+    /// Retroflex approximant (IPA: ɻ)
+    RetroflexApproximant,
+    /// Palatal approximant (IPA: j)
+    PalatalApproximant,
+    /// Velar approximant (IPA: ɰ)
+    VelarApproximant,
+    /// Labial-velar approximant (IPA: w)
+    LabialVelarApproximant,
+    /// Alveolar lateral approximant (IPA: l)
+    AlveolarLateralApproximant,
+    /// Retroflex lateral approximant (IPA: ɭ)
+    RetroflexLateralApproximant,
+    /// Palatal lateral approximant (IPA: ʎ)
+    PalatalLateralApproximant,
+    /// Velar lateral approximant (IPA: ʟ)
+    VelarLateralApproximant,
+    /// Voiceless alveolar affricate (IPA: t͡s)
+    VoicelessAlveolarAffricate,
+    /// Voiced alveolar affricate (IPA: d͡z)
+    VoicedAlveolarAffricate,
+    /// Voiceless postalveolar affricate (IPA: t͡ʃ)
+    VoicelessPostalveolarAffricate,
+    /// Voiced postalveolar affricate (IPA: d͡ʒ)
+    VoicedPostalveolarAffricate,
+    /// Voiceless alveolo-palatal affricate (IPA: t͡ɕ)
+    VoicelessAlveoloPalatalAffricate,
+    /// Voiced alveolo-palatal affricate (IPA: d͡ʑ)
+    VoicedAlveoloPalatalAffricate,
+    /// Voiceless retroflex affricate (IPA: ʈ͡ʂ)
+    VoicelessRetroflexAffricate,
+    /// Voiced retroflex affricate (IPA: ɖ͡ʐ)
+    VoicedRetroflexAffricate,
+}
+
+/// Place of articulation, from the lips backwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Place {
+    /// Both lips, e.g. p, m.
+    Bilabial,
+    /// Lower lip and upper teeth, e.g. f.
+    Labiodental,
+    /// Tongue and upper teeth, e.g. θ.
+    Dental,
+    /// Tongue and alveolar ridge, e.g. t, s.
+    Alveolar,
+    /// Just behind the alveolar ridge, e.g. ʃ.
+    Postalveolar,
+    /// Tongue tip curled back, e.g. ʈ.
+    Retroflex,
+    /// Tongue blade behind the alveolar ridge, body raised, e.g. ɕ.
+    AlveoloPalatal,
+    /// Tongue and hard palate, e.g. j.
+    Palatal,
+    /// Lips and soft palate at once, e.g. w.
+    LabialVelar,
+    /// Tongue and soft palate, e.g. k.
+    Velar,
+    /// Tongue and uvula, e.g. q.
+    Uvular,
+    /// Root of the tongue and pharynx, e.g. ħ.
+    Pharyngeal,
+    /// Vocal folds, e.g. h.
+    Glottal,
+}
+
+/// Manner of articulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Manner {
+    /// Full closure followed by a release burst.
+    Plosive,
+    /// Oral closure with air through the nose.
+    Nasal,
+    /// Repeated vibration of an articulator.
+    Trill,
+    /// Single brief contact.
+    Tap,
+    /// Turbulent airflow through a narrow channel.
+    Fricative,
+    /// Fricative with air flowing past the sides of the tongue.
+    LateralFricative,
+    /// Articulators close without turbulence.
+    Approximant,
+    /// Approximant with air flowing past the sides of the tongue.
+    LateralApproximant,
+    /// Plosive released into a fricative.
+    Affricate,
+}
 
 impl Consonant {
+    /// Every consonant in the inventory.
+    pub const ALL: &[Consonant] = &[
+        Consonant::VoicelessBilabialPlosive,
+        Consonant::VoicedBilabialPlosive,
+        Consonant::VoicelessAlveolarPlosive,
+        Consonant::VoicedAlveolarPlosive,
+        Consonant::VoicelessRetroflexPlosive,
+        Consonant::VoicedRetroflexPlosive,
+        Consonant::VoicelessPalatalPlosive,
+        Consonant::VoicedPalatalPlosive,
+        Consonant::VoicelessVelarPlosive,
+        Consonant::VoicedVelarPlosive,
+        Consonant::VoicelessUvularPlosive,
+        Consonant::VoicedUvularPlosive,
+        Consonant::GlottalStop,
+        Consonant::BilabialNasal,
+        Consonant::LabiodentalNasal,
+        Consonant::AlveolarNasal,
+        Consonant::RetroflexNasal,
+        Consonant::PalatalNasal,
+        Consonant::VelarNasal,
+        Consonant::UvularNasal,
+        Consonant::BilabialTrill,
+        Consonant::AlveolarTrill,
+        Consonant::UvularTrill,
+        Consonant::LabiodentalFlap,
+        Consonant::AlveolarTap,
+        Consonant::RetroflexFlap,
+        Consonant::VoicelessBilabialFricative,
+        Consonant::VoicedBilabialFricative,
+        Consonant::VoicelessLabiodentalFricative,
+        Consonant::VoicedLabiodentalFricative,
+        Consonant::VoicelessDentalFricative,
+        Consonant::VoicedDentalFricative,
+        Consonant::VoicelessAlveolarFricative,
+        Consonant::VoicedAlveolarFricative,
+        Consonant::VoicelessPostalveolarFricative,
+        Consonant::VoicedPostalveolarFricative,
+        Consonant::VoicelessRetroflexFricative,
+        Consonant::VoicedRetroflexFricative,
+        Consonant::VoicelessAlveoloPalatalFricative,
+        Consonant::VoicedAlveoloPalatalFricative,
+        Consonant::VoicelessPalatalFricative,
+        Consonant::VoicedPalatalFricative,
+        Consonant::VoicelessVelarFricative,
+        Consonant::VoicedVelarFricative,
+        Consonant::VoicelessUvularFricative,
+        Consonant::VoicedUvularFricative,
+        Consonant::VoicelessPharyngealFricative,
+        Consonant::VoicedPharyngealFricative,
+        Consonant::VoicelessGlottalFricative,
+        Consonant::VoicedGlottalFricative,
+        Consonant::VoicelessAlveolarLateralFricative,
+        Consonant::VoicedAlveolarLateralFricative,
+        Consonant::LabiodentalApproximant,
+        Consonant::AlveolarApproximant,
+        Consonant::RetroflexApproximant,
+        Consonant::PalatalApproximant,
+        Consonant::VelarApproximant,
+        Consonant::LabialVelarApproximant,
+        Consonant::AlveolarLateralApproximant,
+        Consonant::RetroflexLateralApproximant,
+        Consonant::PalatalLateralApproximant,
+        Consonant::VelarLateralApproximant,
+        Consonant::VoicelessAlveolarAffricate,
+        Consonant::VoicedAlveolarAffricate,
+        Consonant::VoicelessPostalveolarAffricate,
+        Consonant::VoicedPostalveolarAffricate,
+        Consonant::VoicelessAlveoloPalatalAffricate,
+        Consonant::VoicedAlveoloPalatalAffricate,
+        Consonant::VoicelessRetroflexAffricate,
+        Consonant::VoicedRetroflexAffricate,
+    ];
+
     /// Canonical IPA symbol.
     pub fn ipa(&self) -> &'static str {
-        match *self {}
+        match self {
+            Consonant::VoicelessBilabialPlosive => "p",
+            Consonant::VoicedBilabialPlosive => "b",
+            Consonant::VoicelessAlveolarPlosive => "t",
+            Consonant::VoicedAlveolarPlosive => "d",
+            Consonant::VoicelessRetroflexPlosive => "ʈ",
+            Consonant::VoicedRetroflexPlosive => "ɖ",
+            Consonant::VoicelessPalatalPlosive => "c",
+            Consonant::VoicedPalatalPlosive => "ɟ",
+            Consonant::VoicelessVelarPlosive => "k",
+            Consonant::VoicedVelarPlosive => "ɡ",
+            Consonant::VoicelessUvularPlosive => "q",
+            Consonant::VoicedUvularPlosive => "ɢ",
+            Consonant::GlottalStop => "ʔ",
+            Consonant::BilabialNasal => "m",
+            Consonant::LabiodentalNasal => "ɱ",
+            Consonant::AlveolarNasal => "n",
+            Consonant::RetroflexNasal => "ɳ",
+            Consonant::PalatalNasal => "ɲ",
+            Consonant::VelarNasal => "ŋ",
+            Consonant::UvularNasal => "ɴ",
+            Consonant::BilabialTrill => "ʙ",
+            Consonant::AlveolarTrill => "r",
+            Consonant::UvularTrill => "ʀ",
+            Consonant::LabiodentalFlap => "ⱱ",
+            Consonant::AlveolarTap => "ɾ",
+            Consonant::RetroflexFlap => "ɽ",
+            Consonant::VoicelessBilabialFricative => "ɸ",
+            Consonant::VoicedBilabialFricative => "β",
+            Consonant::VoicelessLabiodentalFricative => "f",
+            Consonant::VoicedLabiodentalFricative => "v",
+            Consonant::VoicelessDentalFricative => "θ",
+            Consonant::VoicedDentalFricative => "ð",
+            Consonant::VoicelessAlveolarFricative => "s",
+            Consonant::VoicedAlveolarFricative => "z",
+            Consonant::VoicelessPostalveolarFricative => "ʃ",
+            Consonant::VoicedPostalveolarFricative => "ʒ",
+            Consonant::VoicelessRetroflexFricative => "ʂ",
+            Consonant::VoicedRetroflexFricative => "ʐ",
+            Consonant::VoicelessAlveoloPalatalFricative => "ɕ",
+            Consonant::VoicedAlveoloPalatalFricative => "ʑ",
+            Consonant::VoicelessPalatalFricative => "ç",
+            Consonant::VoicedPalatalFricative => "ʝ",
+            Consonant::VoicelessVelarFricative => "x",
+            Consonant::VoicedVelarFricative => "ɣ",
+            Consonant::VoicelessUvularFricative => "χ",
+            Consonant::VoicedUvularFricative => "ʁ",
+            Consonant::VoicelessPharyngealFricative => "ħ",
+            Consonant::VoicedPharyngealFricative => "ʕ",
+            Consonant::VoicelessGlottalFricative => "h",
+            Consonant::VoicedGlottalFricative => "ɦ",
+            Consonant::VoicelessAlveolarLateralFricative => "ɬ",
+            Consonant::VoicedAlveolarLateralFricative => "ɮ",
+            Consonant::LabiodentalApproximant => "ʋ",
+            Consonant::AlveolarApproximant => "ɹ",
+            Consonant::RetroflexApproximant => "ɻ",
+            Consonant::PalatalApproximant => "j",
+            Consonant::VelarApproximant => "ɰ",
+            Consonant::LabialVelarApproximant => "w",
+            Consonant::AlveolarLateralApproximant => "l",
+            Consonant::RetroflexLateralApproximant => "ɭ",
+            Consonant::PalatalLateralApproximant => "ʎ",
+            Consonant::VelarLateralApproximant => "ʟ",
+            Consonant::VoicelessAlveolarAffricate => "t͡s",
+            Consonant::VoicedAlveolarAffricate => "d͡z",
+            Consonant::VoicelessPostalveolarAffricate => "t͡ʃ",
+            Consonant::VoicedPostalveolarAffricate => "d͡ʒ",
+            Consonant::VoicelessAlveoloPalatalAffricate => "t͡ɕ",
+            Consonant::VoicedAlveoloPalatalAffricate => "d͡ʑ",
+            Consonant::VoicelessRetroflexAffricate => "ʈ͡ʂ",
+            Consonant::VoicedRetroflexAffricate => "ɖ͡ʐ",
+        }
+    }
+
+    /// Place, manner and voicing.
+    fn features(self) -> (Place, Manner, bool) {
+        match self {
+            Consonant::VoicelessBilabialPlosive => (Place::Bilabial, Manner::Plosive, false),
+            Consonant::VoicedBilabialPlosive => (Place::Bilabial, Manner::Plosive, true),
+            Consonant::VoicelessAlveolarPlosive => (Place::Alveolar, Manner::Plosive, false),
+            Consonant::VoicedAlveolarPlosive => (Place::Alveolar, Manner::Plosive, true),
+            Consonant::VoicelessRetroflexPlosive => (Place::Retroflex, Manner::Plosive, false),
+            Consonant::VoicedRetroflexPlosive => (Place::Retroflex, Manner::Plosive, true),
+            Consonant::VoicelessPalatalPlosive => (Place::Palatal, Manner::Plosive, false),
+            Consonant::VoicedPalatalPlosive => (Place::Palatal, Manner::Plosive, true),
+            Consonant::VoicelessVelarPlosive => (Place::Velar, Manner::Plosive, false),
+            Consonant::VoicedVelarPlosive => (Place::Velar, Manner::Plosive, true),
+            Consonant::VoicelessUvularPlosive => (Place::Uvular, Manner::Plosive, false),
+            Consonant::VoicedUvularPlosive => (Place::Uvular, Manner::Plosive, true),
+            Consonant::GlottalStop => (Place::Glottal, Manner::Plosive, false),
+            Consonant::BilabialNasal => (Place::Bilabial, Manner::Nasal, true),
+            Consonant::LabiodentalNasal => (Place::Labiodental, Manner::Nasal, true),
+            Consonant::AlveolarNasal => (Place::Alveolar, Manner::Nasal, true),
+            Consonant::RetroflexNasal => (Place::Retroflex, Manner::Nasal, true),
+            Consonant::PalatalNasal => (Place::Palatal, Manner::Nasal, true),
+            Consonant::VelarNasal => (Place::Velar, Manner::Nasal, true),
+            Consonant::UvularNasal => (Place::Uvular, Manner::Nasal, true),
+            Consonant::BilabialTrill => (Place::Bilabial, Manner::Trill, true),
+            Consonant::AlveolarTrill => (Place::Alveolar, Manner::Trill, true),
+            Consonant::UvularTrill => (Place::Uvular, Manner::Trill, true),
+            Consonant::LabiodentalFlap => (Place::Labiodental, Manner::Tap, true),
+            Consonant::AlveolarTap => (Place::Alveolar, Manner::Tap, true),
+            Consonant::RetroflexFlap => (Place::Retroflex, Manner::Tap, true),
+            Consonant::VoicelessBilabialFricative => (Place::Bilabial, Manner::Fricative, false),
+            Consonant::VoicedBilabialFricative => (Place::Bilabial, Manner::Fricative, true),
+            Consonant::VoicelessLabiodentalFricative => {
+                (Place::Labiodental, Manner::Fricative, false)
+            }
+            Consonant::VoicedLabiodentalFricative => (Place::Labiodental, Manner::Fricative, true),
+            Consonant::VoicelessDentalFricative => (Place::Dental, Manner::Fricative, false),
+            Consonant::VoicedDentalFricative => (Place::Dental, Manner::Fricative, true),
+            Consonant::VoicelessAlveolarFricative => (Place::Alveolar, Manner::Fricative, false),
+            Consonant::VoicedAlveolarFricative => (Place::Alveolar, Manner::Fricative, true),
+            Consonant::VoicelessPostalveolarFricative => {
+                (Place::Postalveolar, Manner::Fricative, false)
+            }
+            Consonant::VoicedPostalveolarFricative => {
+                (Place::Postalveolar, Manner::Fricative, true)
+            }
+            Consonant::VoicelessRetroflexFricative => (Place::Retroflex, Manner::Fricative, false),
+            Consonant::VoicedRetroflexFricative => (Place::Retroflex, Manner::Fricative, true),
+            Consonant::VoicelessAlveoloPalatalFricative => {
+                (Place::AlveoloPalatal, Manner::Fricative, false)
+            }
+            Consonant::VoicedAlveoloPalatalFricative => {
+                (Place::AlveoloPalatal, Manner::Fricative, true)
+            }
+            Consonant::VoicelessPalatalFricative => (Place::Palatal, Manner::Fricative, false),
+            Consonant::VoicedPalatalFricative => (Place::Palatal, Manner::Fricative, true),
+            Consonant::VoicelessVelarFricative => (Place::Velar, Manner::Fricative, false),
+            Consonant::VoicedVelarFricative => (Place::Velar, Manner::Fricative, true),
+            Consonant::VoicelessUvularFricative => (Place::Uvular, Manner::Fricative, false),
+            Consonant::VoicedUvularFricative => (Place::Uvular, Manner::Fricative, true),
+            Consonant::VoicelessPharyngealFricative => {
+                (Place::Pharyngeal, Manner::Fricative, false)
+            }
+            Consonant::VoicedPharyngealFricative => (Place::Pharyngeal, Manner::Fricative, true),
+            Consonant::VoicelessGlottalFricative => (Place::Glottal, Manner::Fricative, false),
+            Consonant::VoicedGlottalFricative => (Place::Glottal, Manner::Fricative, true),
+            Consonant::VoicelessAlveolarLateralFricative => {
+                (Place::Alveolar, Manner::LateralFricative, false)
+            }
+            Consonant::VoicedAlveolarLateralFricative => {
+                (Place::Alveolar, Manner::LateralFricative, true)
+            }
+            Consonant::LabiodentalApproximant => (Place::Labiodental, Manner::Approximant, true),
+            Consonant::AlveolarApproximant => (Place::Alveolar, Manner::Approximant, true),
+            Consonant::RetroflexApproximant => (Place::Retroflex, Manner::Approximant, true),
+            Consonant::PalatalApproximant => (Place::Palatal, Manner::Approximant, true),
+            Consonant::VelarApproximant => (Place::Velar, Manner::Approximant, true),
+            Consonant::LabialVelarApproximant => (Place::LabialVelar, Manner::Approximant, true),
+            Consonant::AlveolarLateralApproximant => {
+                (Place::Alveolar, Manner::LateralApproximant, true)
+            }
+            Consonant::RetroflexLateralApproximant => {
+                (Place::Retroflex, Manner::LateralApproximant, true)
+            }
+            Consonant::PalatalLateralApproximant => {
+                (Place::Palatal, Manner::LateralApproximant, true)
+            }
+            Consonant::VelarLateralApproximant => (Place::Velar, Manner::LateralApproximant, true),
+            Consonant::VoicelessAlveolarAffricate => (Place::Alveolar, Manner::Affricate, false),
+            Consonant::VoicedAlveolarAffricate => (Place::Alveolar, Manner::Affricate, true),
+            Consonant::VoicelessPostalveolarAffricate => {
+                (Place::Postalveolar, Manner::Affricate, false)
+            }
+            Consonant::VoicedPostalveolarAffricate => {
+                (Place::Postalveolar, Manner::Affricate, true)
+            }
+            Consonant::VoicelessAlveoloPalatalAffricate => {
+                (Place::AlveoloPalatal, Manner::Affricate, false)
+            }
+            Consonant::VoicedAlveoloPalatalAffricate => {
+                (Place::AlveoloPalatal, Manner::Affricate, true)
+            }
+            Consonant::VoicelessRetroflexAffricate => (Place::Retroflex, Manner::Affricate, false),
+            Consonant::VoicedRetroflexAffricate => (Place::Retroflex, Manner::Affricate, true),
+        }
+    }
+
+    /// Where the consonant is articulated.
+    pub fn place(self) -> Place {
+        self.features().0
+    }
+
+    /// How the consonant is articulated.
+    pub fn manner(self) -> Manner {
+        self.features().1
+    }
+
+    /// Whether the vocal folds vibrate.
+    pub fn is_voiced(self) -> bool {
+        self.features().2
+    }
+
+    /// Whether the consonant is a sonorant (nasal, trill, tap or approximant),
+    /// which can carry a syllable like a vowel.
+    pub fn is_sonorant(self) -> bool {
+        matches!(
+            self.manner(),
+            Manner::Nasal
+                | Manner::Trill
+                | Manner::Tap
+                | Manner::Approximant
+                | Manner::LateralApproximant
+        )
     }
 }
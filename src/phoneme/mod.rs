@@ -1,3 +1,4 @@
 //! Phoneme stuff.
 
 pub mod ipa;
+pub mod parse;
@@ -0,0 +1,388 @@
+//! Parsing phoneme sequences from IPA and X-SAMPA strings.
+
+use std::fmt::Display;
+
+use crate::phoneme::ipa::{Consonant, Phoneme, Vowel};
+
+/// An unknown symbol in a phoneme string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset of the symbol in the input.
+    pub offset: usize,
+    /// Character (code point) index of the symbol in the input.
+    pub index: usize,
+    /// The unknown symbol, including any combining marks attached to it.
+    pub symbol: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown symbol {:?} at character {}",
+            self.symbol, self.index
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Tie bars joining the two halves of an affricate.
+const IPA_TIES: [char; 2] = ['\u{361}', '\u{35c}'];
+
+/// IPA marks that are accepted but carry no information for synthesis: length
+/// marks (durations come from the score), stress marks and syllable breaks.
+const IPA_IGNORED: [char; 5] = ['ː', 'ˑ', 'ˈ', 'ˌ', '.'];
+
+/// Shorthand for the symbol tables.
+const fn v(vowel: Vowel) -> Phoneme {
+    Phoneme::Vowel(vowel)
+}
+
+/// Shorthand for the symbol tables.
+const fn c(consonant: Consonant) -> Phoneme {
+    Phoneme::Consonant(consonant)
+}
+
+/// Spellings accepted in addition to each phoneme's canonical symbol.
+const IPA_ALIASES: &[(&str, Phoneme)] = &[
+    ("g", c(Consonant::VoicedVelarPlosive)),
+    ("a\u{308}", v(Vowel::OpenCentralUnrounded)),
+    ("ʦ", c(Consonant::VoicelessAlveolarAffricate)),
+    ("ʣ", c(Consonant::VoicedAlveolarAffricate)),
+    ("ʧ", c(Consonant::VoicelessPostalveolarAffricate)),
+    ("ʤ", c(Consonant::VoicedPostalveolarAffricate)),
+    ("ʨ", c(Consonant::VoicelessAlveoloPalatalAffricate)),
+    ("ʥ", c(Consonant::VoicedAlveoloPalatalAffricate)),
+];
+
+/// X-SAMPA marks that are accepted and ignored, like [`IPA_IGNORED`]. Longest
+/// first.
+const XSAMPA_IGNORED: &[&str] = &[":\\", ":", "\"", "%", "."];
+
+/// X-SAMPA symbols. Affricates are written with the `_` tie, e.g. `t_S`.
+const XSAMPA: &[(&str, Phoneme)] = &[
+    ("i", v(Vowel::CloseFrontUnrounded)),
+    ("y", v(Vowel::CloseFrontRounded)),
+    ("1", v(Vowel::CloseCentralUnrounded)),
+    ("}", v(Vowel::CloseCentralRounded)),
+    ("M", v(Vowel::CloseBackUnrounded)),
+    ("u", v(Vowel::CloseBackRounded)),
+    ("I", v(Vowel::NearCloseNearFrontUnrounded)),
+    ("Y", v(Vowel::NearCloseNearFrontRounded)),
+    ("U", v(Vowel::NearCloseNearBackRounded)),
+    ("e", v(Vowel::CloseMidFrontUnrounded)),
+    ("2", v(Vowel::CloseMidFrontRounded)),
+    ("@\\", v(Vowel::CloseMidCentralUnrounded)),
+    ("8", v(Vowel::CloseMidCentralRounded)),
+    ("7", v(Vowel::CloseMidBackUnrounded)),
+    ("o", v(Vowel::CloseMidBackRounded)),
+    ("@", v(Vowel::MidCentral)),
+    ("@`", v(Vowel::RhoticMidCentral)),
+    ("E", v(Vowel::OpenMidFrontUnrounded)),
+    ("9", v(Vowel::OpenMidFrontRounded)),
+    ("3", v(Vowel::OpenMidCentralUnrounded)),
+    ("3\\", v(Vowel::OpenMidCentralRounded)),
+    ("3`", v(Vowel::RhoticOpenMidCentral)),
+    ("V", v(Vowel::OpenMidBackUnrounded)),
+    ("O", v(Vowel::OpenMidBackRounded)),
+    ("{", v(Vowel::NearOpenFrontUnrounded)),
+    ("6", v(Vowel::NearOpenCentral)),
+    ("a", v(Vowel::OpenFrontUnrounded)),
+    ("&", v(Vowel::OpenFrontRounded)),
+    ("a_\"", v(Vowel::OpenCentralUnrounded)),
+    ("A", v(Vowel::OpenBackUnrounded)),
+    ("Q", v(Vowel::OpenBackRounded)),
+    ("p", c(Consonant::VoicelessBilabialPlosive)),
+    ("b", c(Consonant::VoicedBilabialPlosive)),
+    ("t", c(Consonant::VoicelessAlveolarPlosive)),
+    ("d", c(Consonant::VoicedAlveolarPlosive)),
+    ("t`", c(Consonant::VoicelessRetroflexPlosive)),
+    ("d`", c(Consonant::VoicedRetroflexPlosive)),
+    ("c", c(Consonant::VoicelessPalatalPlosive)),
+    ("J\\", c(Consonant::VoicedPalatalPlosive)),
+    ("k", c(Consonant::VoicelessVelarPlosive)),
+    ("g", c(Consonant::VoicedVelarPlosive)),
+    ("q", c(Consonant::VoicelessUvularPlosive)),
+    ("G\\", c(Consonant::VoicedUvularPlosive)),
+    ("?", c(Consonant::GlottalStop)),
+    ("m", c(Consonant::BilabialNasal)),
+    ("F", c(Consonant::LabiodentalNasal)),
+    ("n", c(Consonant::AlveolarNasal)),
+    ("n`", c(Consonant::RetroflexNasal)),
+    ("J", c(Consonant::PalatalNasal)),
+    ("N", c(Consonant::VelarNasal)),
+    ("N\\", c(Consonant::UvularNasal)),
+    ("B\\", c(Consonant::BilabialTrill)),
+    ("r", c(Consonant::AlveolarTrill)),
+    ("R\\", c(Consonant::UvularTrill)),
+    ("4", c(Consonant::AlveolarTap)),
+    ("r`", c(Consonant::RetroflexFlap)),
+    ("p\\", c(Consonant::VoicelessBilabialFricative)),
+    ("B", c(Consonant::VoicedBilabialFricative)),
+    ("f", c(Consonant::VoicelessLabiodentalFricative)),
+    ("v", c(Consonant::VoicedLabiodentalFricative)),
+    ("T", c(Consonant::VoicelessDentalFricative)),
+    ("D", c(Consonant::VoicedDentalFricative)),
+    ("s", c(Consonant::VoicelessAlveolarFricative)),
+    ("z", c(Consonant::VoicedAlveolarFricative)),
+    ("S", c(Consonant::VoicelessPostalveolarFricative)),
+    ("Z", c(Consonant::VoicedPostalveolarFricative)),
+    ("s`", c(Consonant::VoicelessRetroflexFricative)),
+    ("z`", c(Consonant::VoicedRetroflexFricative)),
+    ("s\\", c(Consonant::VoicelessAlveoloPalatalFricative)),
+    ("z\\", c(Consonant::VoicedAlveoloPalatalFricative)),
+    ("C", c(Consonant::VoicelessPalatalFricative)),
+    ("j\\", c(Consonant::VoicedPalatalFricative)),
+    ("x", c(Consonant::VoicelessVelarFricative)),
+    ("G", c(Consonant::VoicedVelarFricative)),
+    ("X", c(Consonant::VoicelessUvularFricative)),
+    ("R", c(Consonant::VoicedUvularFricative)),
+    ("X\\", c(Consonant::VoicelessPharyngealFricative)),
+    ("?\\", c(Consonant::VoicedPharyngealFricative)),
+    ("h", c(Consonant::VoicelessGlottalFricative)),
+    ("h\\", c(Consonant::VoicedGlottalFricative)),
+    ("K", c(Consonant::VoicelessAlveolarLateralFricative)),
+    ("K\\", c(Consonant::VoicedAlveolarLateralFricative)),
+    ("P", c(Consonant::LabiodentalApproximant)),
+    ("v\\", c(Consonant::LabiodentalApproximant)),
+    ("r\\", c(Consonant::AlveolarApproximant)),
+    ("r\\`", c(Consonant::RetroflexApproximant)),
+    ("j", c(Consonant::PalatalApproximant)),
+    ("M\\", c(Consonant::VelarApproximant)),
+    ("w", c(Consonant::LabialVelarApproximant)),
+    ("l", c(Consonant::AlveolarLateralApproximant)),
+    ("l`", c(Consonant::RetroflexLateralApproximant)),
+    ("L", c(Consonant::PalatalLateralApproximant)),
+    ("L\\", c(Consonant::VelarLateralApproximant)),
+    ("t_s", c(Consonant::VoicelessAlveolarAffricate)),
+    ("d_z", c(Consonant::VoicedAlveolarAffricate)),
+    ("t_S", c(Consonant::VoicelessPostalveolarAffricate)),
+    ("d_Z", c(Consonant::VoicedPostalveolarAffricate)),
+    ("t_s\\", c(Consonant::VoicelessAlveoloPalatalAffricate)),
+    ("d_z\\", c(Consonant::VoicedAlveoloPalatalAffricate)),
+    ("t`_s`", c(Consonant::VoicelessRetroflexAffricate)),
+    ("d`_z`", c(Consonant::VoicedRetroflexAffricate)),
+];
+
+impl Phoneme {
+    /// Looks up the phoneme whose X-SAMPA symbol is exactly `symbol`.
+    pub fn from_xsampa(symbol: &str) -> Option<Phoneme> {
+        if symbol == " " {
+            return Some(Phoneme::Space);
+        }
+
+        XSAMPA
+            .iter()
+            .find(|(s, _)| *s == symbol)
+            .map(|&(_, phoneme)| phoneme)
+    }
+}
+
+/// Whether `c` is a combining mark (diacritic or tie bar).
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}')
+}
+
+/// Parses an IPA string such as `"kɑ ti"` into phonemes.
+///
+/// Whitespace separates words and becomes a single [`Phoneme::Space`].
+/// Affricates may be written with a tie bar (`t͡ʃ`, `t͜ʃ`) or as ligatures
+/// (`ʧ`); without a tie, `tʃ` is two phonemes. Length marks, stress marks and
+/// syllable breaks are accepted and dropped, since durations come from the
+/// score.
+///
+/// # Errors
+/// Returns a [`ParseError`] at the first symbol that isn't recognised.
+pub fn parse_ipa(input: &str) -> Result<Vec<Phoneme>, ParseError> {
+    let mut symbols = Vowel::ALL
+        .iter()
+        .map(|&v| (v.ipa().to_owned(), Phoneme::Vowel(v)))
+        .chain(
+            Consonant::ALL
+                .iter()
+                .map(|&c| (c.ipa().to_owned(), Phoneme::Consonant(c))),
+        )
+        .chain(IPA_ALIASES.iter().map(|&(s, p)| (s.to_owned(), p)))
+        .collect::<Vec<_>>();
+    // Alternative tie bar.
+    let alt_ties = symbols
+        .iter()
+        .filter(|(s, _)| s.contains(IPA_TIES[0]))
+        .map(|(s, p)| (s.replace(IPA_TIES[0], &IPA_TIES[1].to_string()), *p))
+        .collect::<Vec<_>>();
+    symbols.extend(alt_ties);
+
+    tokenize(input, &symbols, |rest| {
+        rest.chars()
+            .next()
+            .filter(|c| IPA_IGNORED.contains(c))
+            .map(char::len_utf8)
+    })
+}
+
+/// Parses an X-SAMPA string such as `"kA ti"` into phonemes. See
+/// [`parse_ipa`] for how spaces and suprasegmental marks are handled;
+/// affricates are tied with `_`, e.g. `t_S`.
+///
+/// # Errors
+/// Returns a [`ParseError`] at the first symbol that isn't recognised.
+pub fn parse_xsampa(input: &str) -> Result<Vec<Phoneme>, ParseError> {
+    let symbols = XSAMPA
+        .iter()
+        .map(|&(s, p)| (s.to_owned(), p))
+        .collect::<Vec<_>>();
+
+    tokenize(input, &symbols, |rest| {
+        XSAMPA_IGNORED
+            .iter()
+            .find(|m| rest.starts_with(*m))
+            .map(|m| m.len())
+    })
+}
+
+/// Greedy longest-match tokenizer shared by the IPA and X-SAMPA parsers.
+/// `ignored` returns the length of an ignorable mark at the start of its
+/// argument.
+fn tokenize(
+    input: &str,
+    symbols: &[(String, Phoneme)],
+    ignored: impl Fn(&str) -> Option<usize>,
+) -> Result<Vec<Phoneme>, ParseError> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+
+    while offset < input.len() {
+        let rest = &input[offset..];
+
+        if rest.starts_with(char::is_whitespace) {
+            if out.last().is_some_and(|&p| p != Phoneme::Space) {
+                out.push(Phoneme::Space);
+            }
+            offset += rest.len() - rest.trim_start().len();
+            continue;
+        }
+
+        if let Some(len) = ignored(rest) {
+            offset += len;
+            continue;
+        }
+
+        let matched = symbols
+            .iter()
+            .filter(|(s, _)| rest.starts_with(s.as_str()))
+            .max_by_key(|(s, _)| s.len())
+            // A symbol followed by a diacritic it doesn't include is a
+            // different, unsupported, sound.
+            .filter(|(s, _)| !rest[s.len()..].starts_with(is_combining));
+
+        match matched {
+            Some((symbol, phoneme)) => {
+                out.push(*phoneme);
+                offset += symbol.len();
+            }
+            None => return Err(unknown_symbol(input, offset)),
+        }
+    }
+
+    if out.last() == Some(&Phoneme::Space) {
+        out.pop();
+    }
+
+    Ok(out)
+}
+
+/// Builds the error for an unknown symbol starting at `offset`: the base
+/// character, its combining marks and, across a tie bar, the tied character.
+fn unknown_symbol(input: &str, offset: usize) -> ParseError {
+    let mut chars = input[offset..].chars().peekable();
+    let mut symbol = String::new();
+    if let Some(c) = chars.next() {
+        symbol.push(c);
+    }
+    while let Some(&c) = chars.peek() {
+        if !is_combining(c) {
+            break;
+        }
+        symbol.push(c);
+        chars.next();
+        if IPA_TIES.contains(&c)
+            && let Some(tied) = chars.next()
+        {
+            symbol.push(tied);
+        }
+    }
+
+    ParseError {
+        offset,
+        index: input[..offset].chars().count(),
+        symbol,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const K: Phoneme = c(Consonant::VoicelessVelarPlosive);
+    const A: Phoneme = v(Vowel::OpenBackUnrounded);
+    const I: Phoneme = v(Vowel::CloseFrontUnrounded);
+    const T: Phoneme = c(Consonant::VoicelessAlveolarPlosive);
+    const SH: Phoneme = c(Consonant::VoicelessPostalveolarFricative);
+    const TSH: Phoneme = c(Consonant::VoicelessPostalveolarAffricate);
+
+    #[test]
+    fn ipa() {
+        assert_eq!(parse_ipa("kɑ ti"), Ok(vec![K, A, Phoneme::Space, T, I]));
+        assert_eq!(parse_ipa("  ˈkɑː.ti  \t"), Ok(vec![K, A, T, I]));
+        assert_eq!(parse_ipa(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn ipa_affricates() {
+        assert_eq!(parse_ipa("t͡ʃ"), Ok(vec![TSH]));
+        assert_eq!(parse_ipa("t͜ʃ"), Ok(vec![TSH]));
+        assert_eq!(parse_ipa("ʧ"), Ok(vec![TSH]));
+        assert_eq!(parse_ipa("tʃ"), Ok(vec![T, SH]));
+    }
+
+    #[test]
+    fn ipa_errors() {
+        assert_eq!(
+            parse_ipa("kɑ#"),
+            Err(ParseError {
+                offset: 3,
+                index: 2,
+                symbol: "#".to_owned(),
+            })
+        );
+        // Offsets are in bytes, indices in characters.
+        assert_eq!(
+            parse_ipa("ɑkʰ"),
+            Err(ParseError {
+                offset: 3,
+                index: 2,
+                symbol: "ʰ".to_owned(),
+            })
+        );
+        // Diacritics the inventory doesn't have make the symbol unknown.
+        let err = parse_ipa("ɑk\u{303}i").expect_err("unknown diacritic");
+        assert_eq!((err.offset, err.index), (2, 1));
+        assert_eq!(err.symbol, "k\u{303}");
+    }
+
+    #[test]
+    fn xsampa() {
+        assert_eq!(parse_xsampa("kA ti"), Ok(vec![K, A, Phoneme::Space, T, I]));
+        assert_eq!(parse_xsampa("\"kA:.ti"), Ok(vec![K, A, T, I]));
+        assert_eq!(parse_xsampa("t_S tS"), Ok(vec![TSH, Phoneme::Space, T, SH]));
+        assert_eq!(
+            parse_xsampa("kA!"),
+            Err(ParseError {
+                offset: 2,
+                index: 2,
+                symbol: "!".to_owned(),
+            })
+        );
+    }
+}
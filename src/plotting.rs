@@ -4,19 +4,27 @@ use std::{error::Error, ops::Range};
 
 use plotters::prelude::*;
 
+/// A line chart rendered to a bitmap, used to debug the DSP. The file is
+/// written when the plot is dropped.
 pub struct Plot<'a> {
-    root: DrawingArea<BitMapBackend<'a>, plotters::coord::Shift>,
+    /// The chart being drawn on.
     chart: ChartContext<
         'a,
         BitMapBackend<'a>,
         Cartesian2d<plotters::coord::types::RangedCoordf32, plotters::coord::types::RangedCoordf32>,
     >,
+    /// Range of the x axis.
     x_range: Range<f32>,
-    y_range: Range<f32>,
+    /// Distance between the x values functions are sampled at.
     x_step: f32,
 }
 
 impl<'a> Plot<'a> {
+    /// Creates a plot covering `x_range` and `y_range`, written to
+    /// `file_name`. Functions are sampled every `x_step`.
+    ///
+    /// # Errors
+    /// Returns an error if the chart can't be drawn.
     pub fn new(
         file_name: &'a str,
         caption: impl AsRef<str>,
@@ -31,18 +39,21 @@ impl<'a> Plot<'a> {
             .margin(5)
             .x_label_area_size(30)
             .y_label_area_size(30)
-            .build_cartesian_2d(x_range.clone(), y_range.clone())?;
+            .build_cartesian_2d(x_range.clone(), y_range)?;
 
         chart.configure_mesh().draw()?;
 
         Ok(Self {
-            root,
             chart,
             x_range,
-            y_range,
             x_step,
         })
     }
+
+    /// Draws `func` as a line.
+    ///
+    /// # Errors
+    /// Returns an error if the series can't be drawn.
     pub fn plot(
         &mut self,
         mut func: impl FnMut(f32) -> f32,
@@ -62,6 +73,11 @@ impl<'a> Plot<'a> {
             .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], style.clone()));
         Ok(())
     }
+
+    /// Draws `func` as points.
+    ///
+    /// # Errors
+    /// Returns an error if the series can't be drawn.
     pub fn plot_points(
         &mut self,
         mut func: impl FnMut(f32) -> f32,
@@ -0,0 +1,161 @@
+//! Declarative voicebank manifests (`voice.toml`).
+//!
+//! ```toml
+//! name = "Example"
+//! language = "en"
+//!
+//! [phonemes."ɑ"]
+//! file = "vowel_ɑ.wav"
+//! pitch = 57.0
+//! voiced = [0.02, 0.38]
+//! loop = [0.1, 0.3]
+//! gain = 0.8
+//!
+//! [phonemes.s]
+//! file = "consonant_s.wav"
+//! preutterance = 0.08
+//! overlap = 0.02
+//!
+//! [analysis]
+//! min_f0 = 150.0
+//! max_f0 = 1200.0
+//! ```
+//!
+//! Phonemes are keyed by IPA symbol. Times are in seconds from the start of
+//! the file. An `[analysis]` table sets the pitch analysis of the whole
+//! voicebank; one inside a phoneme's table overrides it for that phoneme.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    dsp::window_calc::AnalysisOptions,
+    error::{Result, VoxlabError},
+    phoneme::ipa::Phoneme,
+};
+
+/// File name of the manifest inside a voicebank directory.
+pub const MANIFEST_FILE: &str = "voice.toml";
+
+/// A voicebank manifest.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Display name of the voicebank.
+    pub name: Option<String>,
+    /// Default language of the samples, as a BCP 47 tag.
+    pub language: Option<String>,
+    /// Samples, keyed by IPA symbol.
+    #[serde(default)]
+    pub phonemes: HashMap<String, SampleEntry>,
+    /// Pitch analysis of every sample.
+    pub analysis: Option<AnalysisEntry>,
+}
+
+/// A single sample in a [`Manifest`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleEntry {
+    /// Sample file, relative to the manifest.
+    pub file: PathBuf,
+    /// Base pitch of the recording as a (fractional) MIDI note. Estimated if
+    /// absent.
+    pub pitch: Option<f32>,
+    /// Start and end of the voiced region. Detected if absent.
+    pub voiced: Option<[f32; 2]>,
+    /// Start and end of the region that's stretched to fit notes. Everything
+    /// before and after it plays at its recorded speed.
+    #[serde(rename = "loop")]
+    pub loop_points: Option<[f32; 2]>,
+    /// Linear gain applied on load.
+    #[serde(default = "unity")]
+    pub gain: f32,
+    /// How long before the note onset the sample starts, so that the point
+    /// this far in lands on the onset.
+    pub preutterance: Option<f32>,
+    /// Length of the crossfade with the previous note.
+    pub overlap: Option<f32>,
+    /// Language of the sample, overriding the manifest's.
+    pub language: Option<String>,
+    /// Pitch analysis of the sample, overriding the manifest's.
+    pub analysis: Option<AnalysisEntry>,
+}
+
+/// Pitch analysis settings in a [`Manifest`]. Anything left out keeps its
+/// previous value; see [`AnalysisOptions`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalysisEntry {
+    /// Lowest fundamental frequency searched for, in Hz.
+    pub min_f0: Option<f32>,
+    /// Highest fundamental frequency searched for, in Hz.
+    pub max_f0: Option<f32>,
+    /// Length of an analysis frame in samples.
+    pub window: Option<usize>,
+    /// Distance between analysis frames in samples.
+    pub hop: Option<usize>,
+}
+
+impl AnalysisEntry {
+    /// Returns `base` with the settings given here replaced.
+    pub fn apply(&self, base: AnalysisOptions) -> AnalysisOptions {
+        AnalysisOptions {
+            min_f0: self.min_f0.unwrap_or(base.min_f0),
+            max_f0: self.max_f0.unwrap_or(base.max_f0),
+            window: self.window.unwrap_or(base.window),
+            hop: self.hop.unwrap_or(base.hop),
+            method: base.method,
+        }
+    }
+}
+
+/// Default for [`SampleEntry::gain`].
+fn unity() -> f32 {
+    1.0
+}
+
+impl Manifest {
+    /// Parses a manifest, resolving sample files against `dir`.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::Manifest`] if the manifest is malformed.
+    pub fn parse(text: &str, dir: &Path) -> Result<Manifest> {
+        let mut manifest: Manifest =
+            toml::from_str(text).map_err(|err| VoxlabError::Manifest(err.to_string()))?;
+        for entry in manifest.phonemes.values_mut() {
+            entry.file = dir.join(&entry.file);
+        }
+        Ok(manifest)
+    }
+
+    /// Reads and parses a manifest file.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or is malformed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Manifest> {
+        let path = path.as_ref();
+        Self::parse(
+            &std::fs::read_to_string(path)?,
+            path.parent().unwrap_or(Path::new("")),
+        )
+    }
+
+    /// Resolves the IPA keys to phonemes.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::Manifest`] if a key isn't an IPA symbol.
+    pub fn entries(&self) -> Result<HashMap<Phoneme, SampleEntry>> {
+        self.phonemes
+            .iter()
+            .map(|(symbol, entry)| {
+                let phoneme = Phoneme::from_ipa(symbol)
+                    .ok_or_else(|| VoxlabError::Manifest(format!("unknown phoneme {symbol:?}")))?;
+                Ok((phoneme, entry.clone()))
+            })
+            .collect()
+    }
+}
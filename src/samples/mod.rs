@@ -0,0 +1,406 @@
+//! Sample loading.
+
+pub mod manifest;
+pub mod oto;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    audio::{
+        MidiNote,
+        buffer::{AudioBuffer, Downmix},
+        wav,
+    },
+    dsp::{
+        gci::gci_pitch_marks,
+        psola::get_avg_period_with,
+        voicing::{VoicingOptions, segment_frames, voiced_period},
+        window_calc::{AnalysisOptions, analyse_pitch, find_window_with},
+    },
+    error::{Result, VoxlabError},
+    phoneme::ipa::Phoneme,
+    samples::{
+        manifest::{MANIFEST_FILE, Manifest, SampleEntry},
+        oto::{OtoEntry, load_oto, ms_to_samples},
+    },
+};
+
+/// Defines a method computing a per-phoneme value once and caching it in
+/// `self.$prop`.
+macro_rules! cached_func {
+    ($(#[$meta:meta])* $name:ident ($prop:ident) -> $ty:ty => $calc:expr) => {
+        $(#[$meta])*
+        ///
+        /// # Errors
+        /// Returns an error if the sample can't be loaded or analysed.
+        pub fn $name (&mut self, phoneme: Phoneme) -> Result<&$ty> {
+            if !self.$prop.contains_key(&phoneme) {
+                let res = $calc(self, phoneme)?;
+                self.$prop.insert(phoneme, res);
+            }
+            Ok(&self.$prop[&phoneme])
+        }
+    };
+    ($(#[$meta:meta])* $name:ident -> $ty:ty => $calc:expr) => {
+        cached_func!($(#[$meta])* $name ($name) -> $ty => $calc);
+    };
+}
+
+/// Where the parts of a phoneme's sample are, in samples from the start of the
+/// (cropped) sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleTiming {
+    /// End of the fixed region at the start of the sample. It's played at its
+    /// original speed; only the region up to `release` is stretched to fit the
+    /// note.
+    pub fixed: usize,
+    /// Start of the release region at the end of the sample, which is also
+    /// played at its original speed.
+    pub release: usize,
+    /// Point that should land on the note onset.
+    pub preutterance: usize,
+    /// Length of the crossfade with the previous note.
+    pub overlap: usize,
+}
+
+/// A voicebank. Samples come from, in order of preference, the manifest (see
+/// [`Voice::load`]), `oto.ini` entries and finally the `vowel_<ipa>.wav` /
+/// `consonant_<ipa>.wav` naming convention under the root directory.
+#[derive(Clone, Debug)]
+pub struct Voice {
+    /// Directory samples are looked for in by the naming convention.
+    root: PathBuf,
+    /// Default language of the samples.
+    language: Option<String>,
+    /// Samples described by the manifest.
+    manifest: HashMap<Phoneme, SampleEntry>,
+    /// Samples described by `oto.ini` entries.
+    oto: HashMap<Phoneme, OtoEntry>,
+    /// Loaded samples.
+    cache: HashMap<Phoneme, AudioBuffer>,
+    /// Sample rate of rendered audio.
+    sample_rate: u32,
+    /// How multichannel samples are mixed down.
+    downmix: Downmix,
+    /// Known base notes.
+    pitches: HashMap<Phoneme, MidiNote>,
+    /// How samples are analysed, unless overridden per phoneme.
+    analysis: AnalysisOptions,
+    /// Per-phoneme analysis overrides.
+    phoneme_analysis: HashMap<Phoneme, AnalysisOptions>,
+    /// Estimated base notes.
+    base_notes: HashMap<Phoneme, MidiNote>,
+    /// Average pitch periods in samples.
+    periods: HashMap<Phoneme, usize>,
+    /// Pitch marks of each sample.
+    pitch_marks: HashMap<Phoneme, Vec<f32>>,
+}
+
+impl Voice {
+    /// Creates a voice reading samples from `root` by the filename convention,
+    /// with known base notes for some phonemes.
+    pub fn new(
+        root: impl AsRef<Path>,
+        sample_rate: u32,
+        pitches: HashMap<Phoneme, MidiNote>,
+    ) -> Self {
+        Self {
+            root: root.as_ref().to_owned(),
+            language: None,
+            manifest: HashMap::new(),
+            oto: HashMap::new(),
+            cache: HashMap::new(),
+            sample_rate,
+            downmix: Downmix::default(),
+            pitches,
+            analysis: AnalysisOptions::default(),
+            phoneme_analysis: HashMap::new(),
+            base_notes: HashMap::new(),
+            periods: HashMap::new(),
+            pitch_marks: HashMap::new(),
+        }
+    }
+    /// Loads the voicebank in `root`, reading its manifest (`voice.toml`) if
+    /// there is one. Phonemes the manifest doesn't list use the filename
+    /// convention.
+    ///
+    /// # Errors
+    /// Returns an error if the manifest can't be read or is malformed.
+    pub fn load(root: impl AsRef<Path>, sample_rate: u32) -> Result<Self> {
+        let mut voice = Self::new(root, sample_rate, HashMap::new());
+        let path = voice.root.join(MANIFEST_FILE);
+        if path.exists() {
+            voice.set_manifest(&Manifest::load(path)?)?;
+        }
+        Ok(voice)
+    }
+
+    /// Uses the samples described by `manifest`, discarding anything cached
+    /// for them.
+    ///
+    /// # Errors
+    /// Returns [`VoxlabError::Manifest`] if a key isn't an IPA symbol.
+    pub fn set_manifest(&mut self, manifest: &Manifest) -> Result<()> {
+        if let Some(analysis) = &manifest.analysis {
+            self.set_analysis(analysis.apply(self.analysis));
+        }
+        for (phoneme, entry) in manifest.entries()? {
+            self.forget(phoneme);
+            if let Some(pitch) = entry.pitch {
+                self.pitches.insert(phoneme, MidiNote(pitch));
+            }
+            if let Some(analysis) = &entry.analysis {
+                self.set_phoneme_analysis(phoneme, analysis.apply(self.analysis));
+            }
+            self.manifest.insert(phoneme, entry);
+        }
+        self.language.clone_from(&manifest.language);
+        Ok(())
+    }
+
+    /// Language of the specified phoneme's sample, if the manifest gives one.
+    pub fn language(&self, phoneme: Phoneme) -> Option<&str> {
+        self.manifest
+            .get(&phoneme)
+            .and_then(|entry| entry.language.as_deref())
+            .or(self.language.as_deref())
+    }
+
+    /// Sample rate of rendered audio.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// How the specified phoneme's sample is analysed.
+    pub fn analysis(&self, phoneme: Phoneme) -> &AnalysisOptions {
+        self.phoneme_analysis
+            .get(&phoneme)
+            .unwrap_or(&self.analysis)
+    }
+
+    /// Analyses samples with `options`, except for phonemes with their own
+    /// options (see [`Voice::set_phoneme_analysis`]). Discards all analysis
+    /// done so far.
+    pub fn set_analysis(&mut self, options: AnalysisOptions) {
+        self.analysis = options;
+        self.base_notes.clear();
+        self.periods.clear();
+        self.pitch_marks.clear();
+    }
+
+    /// Analyses the specified phoneme's sample with `options`, discarding its
+    /// analysis so far.
+    pub fn set_phoneme_analysis(&mut self, phoneme: Phoneme, options: AnalysisOptions) {
+        self.phoneme_analysis.insert(phoneme, options);
+        self.forget_analysis(phoneme);
+    }
+
+    /// Mixes multichannel samples down with `downmix` (averaging by default),
+    /// discarding every sample loaded so far.
+    pub fn set_downmix(&mut self, downmix: Downmix) {
+        self.downmix = downmix;
+        let loaded = self.cache.keys().copied().collect::<Vec<_>>();
+        for phoneme in loaded {
+            self.forget(phoneme);
+        }
+    }
+
+    /// Imports the sample for `phoneme` from `path`, mixed down and converted
+    /// to the voice's sample rate, reporting a missing file as
+    /// [`VoxlabError::MissingSample`].
+    fn import_sample(&self, phoneme: Phoneme, path: PathBuf) -> Result<AudioBuffer> {
+        if !path.exists() {
+            return Err(VoxlabError::MissingSample { phoneme, path });
+        }
+        wav::import_wav_with(path, self.downmix)?.resample(self.sample_rate)
+    }
+
+    /// Discards everything derived from the specified phoneme's sample.
+    fn forget(&mut self, phoneme: Phoneme) {
+        self.cache.remove(&phoneme);
+        self.forget_analysis(phoneme);
+    }
+
+    /// Discards the analysis of the specified phoneme's sample.
+    fn forget_analysis(&mut self, phoneme: Phoneme) {
+        self.base_notes.remove(&phoneme);
+        self.periods.remove(&phoneme);
+        self.pitch_marks.remove(&phoneme);
+    }
+
+    /// Loads an UTAU `oto.ini`, using every entry whose alias is an IPA symbol
+    /// (see [`Phoneme::from_ipa`]).
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read.
+    pub fn load_oto(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.load_oto_with(path, Phoneme::from_ipa)
+    }
+
+    /// Loads an UTAU `oto.ini`, mapping aliases to phonemes with `resolve`.
+    /// Entries it returns `None` for are skipped. Later entries win over
+    /// earlier ones.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read.
+    pub fn load_oto_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        mut resolve: impl FnMut(&str) -> Option<Phoneme>,
+    ) -> Result<()> {
+        for entry in load_oto(path)? {
+            if let Some(phoneme) = resolve(&entry.alias) {
+                self.insert_oto(phoneme, entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Uses `entry` for `phoneme`, discarding anything cached for it.
+    pub fn insert_oto(&mut self, phoneme: Phoneme, entry: OtoEntry) {
+        self.forget(phoneme);
+        self.oto.insert(phoneme, entry);
+    }
+
+    /// Returns where the fixed and release regions, preutterance and overlap of
+    /// the specified phoneme's sample are. Without a manifest loop or an
+    /// `oto.ini` entry, the whole sample is stretched. A manifest's
+    /// preutterance and overlap take precedence over the `oto.ini`'s.
+    ///
+    /// # Errors
+    /// Returns an error if the sample can't be loaded.
+    pub fn timing(&mut self, phoneme: Phoneme) -> Result<SampleTiming> {
+        let entry = self.manifest.get(&phoneme);
+        let loop_points = entry.and_then(|entry| entry.loop_points);
+        let preutterance = entry.and_then(|entry| entry.preutterance);
+        let overlap = entry.and_then(|entry| entry.overlap);
+        let oto = self.oto.get(&phoneme).cloned();
+
+        let sample = self.sample(phoneme)?;
+        let len = sample.len();
+        let secs = |secs: f32| ((secs.max(0.0) * sample.sample_rate as f32) as usize).min(len);
+        let ms = |ms| ms_to_samples(ms, sample.sample_rate).min(len);
+
+        let mut timing = match (loop_points, oto) {
+            (Some([start, end]), _) => SampleTiming {
+                fixed: secs(start),
+                release: secs(end).max(secs(start)),
+                preutterance: 0,
+                overlap: 0,
+            },
+            (None, Some(entry)) => SampleTiming {
+                fixed: ms(entry.consonant),
+                release: len,
+                preutterance: ms(entry.preutterance),
+                overlap: ms(entry.overlap),
+            },
+            (None, None) => SampleTiming {
+                fixed: 0,
+                release: len,
+                preutterance: 0,
+                overlap: 0,
+            },
+        };
+        if let Some(preutterance) = preutterance {
+            timing.preutterance = secs(preutterance);
+        }
+        if let Some(overlap) = overlap {
+            timing.overlap = secs(overlap);
+        }
+        Ok(timing)
+    }
+    cached_func!(
+        /// Returns the MIDI note number of the specified phoneme, estimating if no
+        /// known note.
+        base_note (base_notes) -> MidiNote => |this: &mut Self, phoneme: Phoneme| -> Result<_> {
+            if let Some(&note) = this.pitches.get(&phoneme) {
+                return Ok(note);
+            }
+            let avg_period = *this.period(phoneme)?;
+            let sample = this.sample(phoneme)?;
+            Ok(MidiNote::from_frequency(sample.sample_rate as f32 / avg_period as f32))
+        }
+    );
+
+    cached_func!(
+        /// Returns the average pitch period of the voiced region of the specified
+        /// phoneme, in samples. Without a region in the manifest, only frames
+        /// classified as voiced (see [`segment_frames`]) are measured.
+        period (periods) -> usize => |this: &mut Self, phoneme: Phoneme| -> Result<_> {
+            let voiced = this.manifest.get(&phoneme).and_then(|entry| entry.voiced);
+            let analysis = *this.analysis(phoneme);
+            let sample = this.sample(phoneme)?;
+            let Some([start, end]) = voiced else {
+                let frames = analyse_pitch(sample, &analysis, None)?;
+                let segments = segment_frames(sample, &frames, &analysis, &VoicingOptions::default());
+                return voiced_period(&frames, &segments, &analysis)
+                    .map(|period| period.round() as usize)
+                    .ok_or(VoxlabError::NoVoicedRegion);
+            };
+
+            let secs = |secs: f32| {
+                ((secs.max(0.0) * sample.sample_rate as f32) as usize).min(sample.len())
+            };
+            let sample = AudioBuffer {
+                sample_rate: sample.sample_rate,
+                samples: sample.samples[secs(start)..secs(end).max(secs(start))].to_vec(),
+            };
+            get_avg_period_with(&sample, &analysis)
+        }
+    );
+
+    cached_func!(
+        /// Returns the pitch marks of the specified phoneme's sample, on its
+        /// glottal closure instants (see [`gci_pitch_marks`]).
+        pitch_marks -> [f32] => |this: &mut Self, phoneme: Phoneme| -> Result<_> {
+            Ok({
+                let analysis = *this.analysis(phoneme);
+                let sample = this.sample(phoneme)?;
+                let windows = find_window_with(sample, &analysis, None)?;
+                gci_pitch_marks(sample, &windows)
+            })
+        }
+    );
+
+    cached_func!(
+        /// Returns the specified phoneme's sample, from the manifest, the
+        /// `oto.ini` or the filename convention, in that order, converted to the
+        /// voice's sample rate.
+        sample (cache) -> AudioBuffer => |this: &mut Self, phoneme: Phoneme| -> Result<_> {
+            if let Some(entry) = this.manifest.get(&phoneme) {
+                let mut sample = this.import_sample(phoneme, entry.file.clone())?;
+                for s in &mut sample.samples {
+                    *s *= entry.gain;
+                }
+                return Ok(sample);
+            }
+
+            if let Some(entry) = this.oto.get(&phoneme) {
+                let sample = this.import_sample(phoneme, entry.file.clone())?;
+                let (start, end) = entry.range(sample.len(), sample.sample_rate);
+                return Ok(AudioBuffer {
+                    sample_rate: sample.sample_rate,
+                    samples: sample.samples[start..end].to_vec(),
+                });
+            }
+
+            Ok(match phoneme {
+                // Rests are silence of whatever length the score asks for.
+                Phoneme::Space => AudioBuffer {
+                    sample_rate: this.sample_rate(),
+                    samples: Vec::new(),
+                },
+                Phoneme::Vowel(_) => {
+                    this.import_sample(phoneme, this.root.join(format!("vowel_{}.wav", phoneme.ipa())))?
+                }
+                Phoneme::Consonant(_) => this.import_sample(
+                    phoneme,
+                    this.root.join(format!("consonant_{}.wav", phoneme.ipa())),
+                )?,
+            })
+        }
+    );
+}
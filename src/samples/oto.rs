@@ -0,0 +1,149 @@
+//! UTAU `oto.ini` voicebank configuration.
+
+use std::path::{Path, PathBuf};
+
+/// One line of an `oto.ini`. All times are in milliseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OtoEntry {
+    /// Sample file, relative to the directory containing the `oto.ini`.
+    pub file: PathBuf,
+    /// Alias the entry is referred to by. Defaults to the file stem.
+    pub alias: String,
+    /// Start of the usable part of the sample, from the start of the file.
+    pub offset: f32,
+    /// Length of the fixed (never stretched) region, from `offset`.
+    pub consonant: f32,
+    /// End of the usable part of the sample. Positive values are measured back
+    /// from the end of the file, negative values forward from `offset`.
+    pub cutoff: f32,
+    /// Position, from `offset`, that lands on the note onset.
+    pub preutterance: f32,
+    /// Length, from `offset`, of the crossfade with the previous note.
+    pub overlap: f32,
+}
+
+impl OtoEntry {
+    /// Parses a single `file.wav=alias,offset,consonant,cutoff,preutterance,overlap`
+    /// line. Missing or invalid numbers are treated as zero, like UTAU does.
+    /// Returns `None` for blank lines and lines without a `=`.
+    pub fn parse(line: &str) -> Option<OtoEntry> {
+        let (file, params) = line.trim().split_once('=')?;
+        let mut params = params.split(',');
+
+        let file = PathBuf::from(file.trim());
+        let alias = match params.next().map(str::trim) {
+            Some(alias) if !alias.is_empty() => alias.to_owned(),
+            _ => file.file_stem()?.to_string_lossy().into_owned(),
+        };
+
+        let mut num = || {
+            params
+                .next()
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .unwrap_or(0.0)
+        };
+
+        Some(OtoEntry {
+            file,
+            alias,
+            offset: num(),
+            consonant: num(),
+            cutoff: num(),
+            preutterance: num(),
+            overlap: num(),
+        })
+    }
+
+    /// Returns the usable range of a sample of `len` samples at `sample_rate`,
+    /// as sample indices.
+    pub fn range(&self, len: usize, sample_rate: u32) -> (usize, usize) {
+        let start = ms_to_samples(self.offset, sample_rate).min(len);
+        let end = if self.cutoff < 0.0 {
+            start + ms_to_samples(-self.cutoff, sample_rate)
+        } else {
+            len.saturating_sub(ms_to_samples(self.cutoff, sample_rate))
+        };
+        (start, end.clamp(start, len))
+    }
+}
+
+/// Converts a length in milliseconds to samples.
+pub fn ms_to_samples(ms: f32, sample_rate: u32) -> usize {
+    (ms.max(0.0) * sample_rate as f32 / 1000.0).round() as usize
+}
+
+/// Parses the contents of an `oto.ini`.
+///
+/// Many voicebanks are Shift-JIS encoded; only UTF-8 is decoded, other bytes
+/// are replaced, so only ASCII (or UTF-8) file names and aliases survive.
+pub fn parse_oto(text: &str) -> Vec<OtoEntry> {
+    text.lines().filter_map(OtoEntry::parse).collect()
+}
+
+/// Reads and parses an `oto.ini`, resolving sample files against its directory.
+///
+/// # Errors
+/// Returns an error if the file can't be read.
+pub fn load_oto(path: impl AsRef<Path>) -> std::io::Result<Vec<OtoEntry>> {
+    let path = path.as_ref();
+    let text = std::fs::read(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    Ok(parse_oto(&String::from_utf8_lossy(&text))
+        .into_iter()
+        .map(|entry| OtoEntry {
+            file: dir.join(&entry.file),
+            ..entry
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries() {
+        let entry = OtoEntry::parse("a.wav=a,120.5,80,-300,60,20").expect("entry");
+        assert_eq!(
+            entry,
+            OtoEntry {
+                file: PathBuf::from("a.wav"),
+                alias: "a".to_owned(),
+                offset: 120.5,
+                consonant: 80.0,
+                cutoff: -300.0,
+                preutterance: 60.0,
+                overlap: 20.0,
+            }
+        );
+    }
+
+    #[test]
+    fn fills_in_missing_fields() {
+        let entry = OtoEntry::parse(" ka.wav=,10,x ").expect("entry");
+        assert_eq!(entry.alias, "ka");
+        assert!((entry.offset - 10.0).abs() < f32::EPSILON);
+        assert!(entry.consonant.abs() < f32::EPSILON);
+        assert!(entry.overlap.abs() < f32::EPSILON);
+
+        assert_eq!(OtoEntry::parse(""), None);
+        assert_eq!(OtoEntry::parse("[#SETTING]"), None);
+        assert_eq!(parse_oto("a.wav=a\n\nb.wav=b\n").len(), 2);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut entry = OtoEntry::parse("a.wav=a,100,0,200").expect("entry");
+        // 1 s at 1 kHz: 100 ms in, stopping 200 ms before the end.
+        assert_eq!(entry.range(1000, 1000), (100, 800));
+        // Negative cutoffs are lengths from the offset.
+        entry.cutoff = -300.0;
+        assert_eq!(entry.range(1000, 1000), (100, 400));
+        // Clamped to the sample.
+        entry.cutoff = -2000.0;
+        assert_eq!(entry.range(1000, 1000), (100, 1000));
+        entry.offset = 2000.0;
+        assert_eq!(entry.range(1000, 1000), (1000, 1000));
+    }
+}
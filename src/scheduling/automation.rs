@@ -0,0 +1,205 @@
+//! Automation lanes drawn with control points.
+
+use crate::scheduling::time::{TempoMap, Time};
+
+/// How the value moves from one control point to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold the value until the next point.
+    Step,
+    /// Straight line to the next point.
+    #[default]
+    Linear,
+    /// Smooth curve through the neighbouring points (Catmull-Rom).
+    Cubic,
+}
+
+/// A point on an automation lane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlPoint {
+    /// Position on the score timeline.
+    pub time: Time,
+    /// Value at this point, in the lane's unit.
+    pub value: f32,
+    /// How the value moves from this point to the next.
+    pub interpolation: Interpolation,
+}
+
+/// A hand-drawn control signal on the score timeline, such as a pitch offset
+/// in cents or a gain in decibels. The value is held before the first point
+/// and after the last, and is zero if there are no points.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Automation {
+    /// The control points, in any order.
+    pub points: Vec<ControlPoint>,
+}
+
+impl Automation {
+    /// Resolves the points' times through `tempo`.
+    pub fn resolve(&self, tempo: &TempoMap) -> AutomationCurve {
+        let mut points = self
+            .points
+            .iter()
+            .map(|point| (tempo.seconds(point.time), point.value, point.interpolation))
+            .collect::<Vec<_>>();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        AutomationCurve { points }
+    }
+}
+
+/// An [`Automation`] lane with its times resolved to seconds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutomationCurve {
+    /// Time in seconds, value and interpolation of each point, sorted by
+    /// time.
+    points: Vec<(f64, f32, Interpolation)>,
+}
+
+impl AutomationCurve {
+    /// Whether the curve has no points, and so is zero everywhere.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Value at `t` seconds.
+    pub fn value_at(&self, t: f64) -> f32 {
+        let points = &self.points;
+        let i = points.partition_point(|&(time, _, _)| time <= t);
+        let (Some(&(t0, v0, interpolation)), Some(&(t1, v1, _))) =
+            (i.checked_sub(1).and_then(|i| points.get(i)), points.get(i))
+        else {
+            // Before the first point or after the last.
+            return points
+                .get(i.saturating_sub(1))
+                .map_or(0.0, |&(_, value, _)| value);
+        };
+
+        let x = ((t - t0) / (t1 - t0)) as f32;
+        match interpolation {
+            Interpolation::Step => v0,
+            Interpolation::Linear => v0 + (v1 - v0) * x,
+            Interpolation::Cubic => {
+                // Tangents from the neighbouring points, flat at the ends,
+                // scaled to this segment's length.
+                let span = (t1 - t0) as f32;
+                let tangent =
+                    |j: usize| match (j.checked_sub(1).map(|j| points[j]), points.get(j + 1)) {
+                        (Some((ta, va, _)), Some(&(tb, vb, _))) => {
+                            (vb - va) / (tb - ta) as f32 * span
+                        }
+                        _ => 0.0,
+                    };
+                let (m0, m1) = (tangent(i - 1), tangent(i));
+                let (x2, x3) = (x * x, x * x * x);
+                (2.0 * x3 - 3.0 * x2 + 1.0) * v0
+                    + (x3 - 2.0 * x2 + x) * m0
+                    + (-2.0 * x3 + 3.0 * x2) * v1
+                    + (x3 - x2) * m1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A curve through `(seconds, value)` points, all interpolated the same
+    /// way.
+    fn curve(points: &[(f64, f32)], interpolation: Interpolation) -> AutomationCurve {
+        Automation {
+            points: points
+                .iter()
+                .map(|&(time, value)| ControlPoint {
+                    time: Time::Seconds(time),
+                    value,
+                    interpolation,
+                })
+                .collect(),
+        }
+        .resolve(&TempoMap::constant(480, 120.0))
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{actual} isn't {expected}"
+        );
+    }
+
+    #[test]
+    fn empty_is_zero() {
+        let curve = AutomationCurve::default();
+        assert!(curve.is_empty());
+        assert_close(curve.value_at(1.0), 0.0);
+    }
+
+    #[test]
+    fn held_outside_points() {
+        for interpolation in [
+            Interpolation::Step,
+            Interpolation::Linear,
+            Interpolation::Cubic,
+        ] {
+            let curve = curve(&[(1.0, 10.0), (2.0, 20.0)], interpolation);
+            assert_close(curve.value_at(0.0), 10.0);
+            assert_close(curve.value_at(1.0), 10.0);
+            assert_close(curve.value_at(2.0), 20.0);
+            assert_close(curve.value_at(5.0), 20.0);
+        }
+    }
+
+    #[test]
+    fn step() {
+        let curve = curve(&[(0.0, 1.0), (1.0, 2.0)], Interpolation::Step);
+        assert_close(curve.value_at(0.999), 1.0);
+        assert_close(curve.value_at(1.0), 2.0);
+    }
+
+    #[test]
+    fn linear() {
+        let curve = curve(
+            &[(0.0, 0.0), (1.0, 100.0), (3.0, 0.0)],
+            Interpolation::Linear,
+        );
+        assert_close(curve.value_at(0.25), 25.0);
+        assert_close(curve.value_at(2.0), 50.0);
+    }
+
+    #[test]
+    fn cubic() {
+        // Flat tangents at the ends make a smoothstep between two points.
+        let two = curve(&[(0.0, 0.0), (1.0, 1.0)], Interpolation::Cubic);
+        assert_close(two.value_at(0.5), 0.5);
+        assert_close(two.value_at(0.25), 0.15625);
+
+        // Points on a line are interpolated along it in the middle segment.
+        let line = curve(
+            &[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)],
+            Interpolation::Cubic,
+        );
+        assert_close(line.value_at(1.5), 1.5);
+        assert_close(line.value_at(1.25), 1.25);
+    }
+
+    #[test]
+    fn resolves_ticks_and_sorts() {
+        let automation = Automation {
+            points: vec![
+                ControlPoint {
+                    time: Time::Ticks(960),
+                    value: 2.0,
+                    interpolation: Interpolation::Linear,
+                },
+                ControlPoint {
+                    time: Time::Seconds(0.0),
+                    value: 0.0,
+                    interpolation: Interpolation::Linear,
+                },
+            ],
+        };
+        // 960 ticks at 480 per quarter and 120 BPM is one second.
+        let curve = automation.resolve(&TempoMap::constant(480, 120.0));
+        assert_close(curve.value_at(0.5), 1.0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Loudness of notes and of the mix.
+
+use crate::nice::lerp;
+
+/// Attack, decay, sustain, release amplitude envelope of a note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdsrOptions {
+    /// Time to rise from silence to full level, in seconds.
+    pub attack: f32,
+    /// Time to fall from full level to the sustain level, in seconds.
+    pub decay: f32,
+    /// Level held for the rest of the note, from 0 to 1.
+    pub sustain: f32,
+    /// Time to fall to silence at the end of the note, in seconds. The
+    /// release is part of the note's duration.
+    pub release: f32,
+}
+
+impl Default for AdsrOptions {
+    /// A 20 ms attack, a 100 ms decay to 80% and a 50 ms release.
+    fn default() -> Self {
+        Self {
+            attack: 0.02,
+            decay: 0.1,
+            sustain: 0.8,
+            release: 0.05,
+        }
+    }
+}
+
+impl AdsrOptions {
+    /// Gain `t` seconds after the onset of a note lasting `duration` seconds.
+    ///
+    /// A note entered through a transition starts at the sustain level, and
+    /// one left through a transition holds it to the end, so legato notes
+    /// don't dip in between.
+    pub fn gain_at(&self, t: f32, duration: f32, legato_in: bool, legato_out: bool) -> f32 {
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        let level = if legato_in || t >= self.attack + self.decay {
+            sustain
+        } else if t < self.attack {
+            t / self.attack
+        } else {
+            lerp(1.0, sustain, (t - self.attack) / self.decay)
+        };
+
+        if legato_out || self.release <= 0.0 {
+            level
+        } else {
+            level * ((duration - t) / self.release).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Converts a gain in decibels to an amplitude factor.
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+/// Scales `samples` down so that none exceeds full scale. Quieter audio is
+/// left alone.
+pub fn limit_peak(samples: &mut [f32]) {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak > 1.0 {
+        for s in samples {
+            *s /= peak;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADSR: AdsrOptions = AdsrOptions {
+        attack: 0.1,
+        decay: 0.2,
+        sustain: 0.5,
+        release: 0.2,
+    };
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{actual} isn't {expected}"
+        );
+    }
+
+    #[test]
+    fn adsr_shape() {
+        let gain = |t| ADSR.gain_at(t, 1.0, false, false);
+        assert_close(gain(0.0), 0.0);
+        assert_close(gain(0.05), 0.5);
+        assert_close(gain(0.1), 1.0);
+        assert_close(gain(0.2), 0.75);
+        assert_close(gain(0.5), 0.5);
+        assert_close(gain(0.9), 0.25);
+        assert_close(gain(1.0), 0.0);
+    }
+
+    #[test]
+    fn legato_carries_the_sustain_level() {
+        // Entered legato: no attack or decay.
+        assert_close(ADSR.gain_at(0.0, 1.0, true, false), 0.5);
+        assert_close(ADSR.gain_at(0.05, 1.0, true, false), 0.5);
+        // Left legato: no release.
+        assert_close(ADSR.gain_at(1.0, 1.0, false, true), 0.5);
+        assert_close(ADSR.gain_at(0.05, 1.0, false, true), 0.5);
+        // A legato event ends at the level the next one starts at.
+        assert_close(
+            ADSR.gain_at(0.5, 0.5, false, true),
+            ADSR.gain_at(0.0, 0.5, true, false),
+        );
+    }
+
+    #[test]
+    fn release_overlaps_a_short_note() {
+        // The release scales whatever level the attack has reached: half
+        // way up, a quarter of the way into the release.
+        assert_close(ADSR.gain_at(0.05, 0.1, false, false), 0.125);
+        let no_release = AdsrOptions {
+            release: 0.0,
+            ..ADSR
+        };
+        assert_close(no_release.gain_at(1.0, 1.0, false, false), 0.5);
+    }
+
+    #[test]
+    fn decibels() {
+        assert_close(db_to_gain(0.0), 1.0);
+        assert_close(db_to_gain(20.0), 10.0);
+        assert!((db_to_gain(-6.0206) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn limiter_keeps_the_balance() {
+        let mut loud = [0.5, -2.0, 1.0, 0.25];
+        limit_peak(&mut loud);
+        for (actual, expected) in loud.into_iter().zip([0.25, -1.0, 0.5, 0.125]) {
+            assert_close(actual, expected);
+        }
+
+        let mut quiet = [0.5, -0.9, 1.0];
+        limit_peak(&mut quiet);
+        for (actual, expected) in quiet.into_iter().zip([0.5, -0.9, 1.0]) {
+            assert_close(actual, expected);
+        }
+    }
+}
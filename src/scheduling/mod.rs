@@ -0,0 +1,740 @@
+//! Scheduling multiple phonemes.
+
+pub mod automation;
+pub mod dynamics;
+pub mod portamento;
+pub mod time;
+pub mod vibrato;
+
+use std::fmt::{Debug, Display};
+
+use crate::{
+    audio::{MidiNote, buffer::AudioBuffer},
+    dsp::{crossfade::GrainInterp, psola::psola_curve, stretch::stretch_unvoiced},
+    error::{Result, VoxlabError},
+    nice::smoothstep,
+    phoneme::ipa::{Manner, Phoneme},
+    samples::Voice,
+    scheduling::{
+        automation::AutomationCurve,
+        dynamics::{AdsrOptions, db_to_gain, limit_peak},
+        portamento::{Glide, PortamentoOptions},
+        time::{TempoMap, Time},
+        vibrato::{Vibrato, VibratoOptions},
+    },
+};
+
+/// Length of the fade applied where a note is cut off without a transition,
+/// in seconds.
+const DECLICK_TIME: f64 = 0.005;
+
+/// The canonical ID of a specific [`PhonemeInstance`]. Should be mostly treated
+/// opaquely, but is an index internally.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct InstanceId(usize);
+
+impl InstanceId {
+    /// Creates an ID from an index.
+    pub fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    /// Returns the index the ID was created from.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Debug for InstanceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("P")?;
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for InstanceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// Per-instance rendering options.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhonemeOptions {
+    /// How to transition into the next phoneme. Cut if `None`.
+    pub next_transition: Option<TransitionOptions>,
+    /// Vibrato on the note, timed from the onset of this instance. Only
+    /// periodic phonemes are affected.
+    pub vibrato: Option<VibratoOptions>,
+    /// Amplitude envelope of the note. Full level throughout if `None`.
+    pub envelope: Option<AdsrOptions>,
+    /// How long before its onset the phoneme starts, in seconds, so that its
+    /// sample's preutterance point lands on the onset. Taken from the voice
+    /// if `None` (see [`Schedule::schedule_for`]).
+    pub preutterance: Option<f64>,
+    /// Length of the crossfade with the previous phoneme, in seconds, which
+    /// is cut short to end this long after this one starts. Taken from the
+    /// voice if `None`.
+    pub overlap: Option<f64>,
+    /// Whether the next phoneme continues the same note, so the envelope
+    /// carries on through the boundary instead of releasing and starting
+    /// over.
+    pub legato_next: bool,
+}
+
+/// Options for a transition between two phonemes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionOptions {
+    /// Length of the transition in pitch periods.
+    pub length_grains: usize,
+    /// Pitch glide into the next note. The pitch jumps in the middle of the
+    /// transition if `None`.
+    pub portamento: Option<PortamentoOptions>,
+}
+
+/// A phoneme placed on the score.
+#[derive(Clone, PartialEq)]
+pub struct PhonemeInstance {
+    /// Identity of this instance.
+    pub instance_id: InstanceId,
+    /// The phoneme to sing.
+    pub phoneme: Phoneme,
+    /// Onset on the score timeline.
+    pub start: Time,
+    /// How long the phoneme lasts, from its onset.
+    pub duration: Time,
+    /// Rendering options.
+    pub options: PhonemeOptions,
+    /// The note to sing at.
+    pub note: MidiNote,
+    /// How loud to sing, as an amplitude factor from 0 to 1.
+    pub velocity: f32,
+}
+
+impl PhonemeInstance {
+    /// Whether this is a rest, which is silent for its whole duration.
+    pub fn is_rest(&self) -> bool {
+        self.phoneme == Phoneme::Space
+    }
+}
+
+impl Debug for PhonemeInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhonemeInstance")
+            .field("instance_id", &self.instance_id)
+            .field("phoneme", &self.phoneme)
+            .field("next_transition", &self.options.next_transition)
+            .field("vibrato", &self.options.vibrato)
+            .field("envelope", &self.options.envelope)
+            .field("preutterance", &self.options.preutterance)
+            .field("overlap", &self.options.overlap)
+            .field("legato_next", &self.options.legato_next)
+            .field("start", &self.start)
+            .field("duration", &self.duration)
+            .field("pitch", &self.note)
+            .field("velocity", &self.velocity)
+            .finish()
+    }
+}
+
+/// One scheduled grain in the output timeline.
+#[derive(Clone, Debug)]
+pub struct GrainEvent {
+    /// Identity of the phoneme instance that produced this grain
+    pub instance_id: InstanceId,
+    /// Source phoneme to read from
+    pub source: Phoneme,
+    /// Onset in seconds.
+    pub start: f64,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// The note that this phoneme should be played at.
+    pub note: MidiNote,
+    /// Transition into the next event, if any. Its `fade_len` counts pitch
+    /// periods rather than samples.
+    pub interp: Option<GrainInterp>,
+    /// Pitch glide into the next event, only used with `interp`.
+    pub portamento: Option<PortamentoOptions>,
+    /// Vibrato, timed from the onset.
+    pub vibrato: Option<VibratoOptions>,
+    /// Amplitude factor of the event.
+    pub velocity: f32,
+    /// Amplitude envelope, timed from the onset.
+    pub envelope: Option<AdsrOptions>,
+    /// Length of the crossfade from the previous event in seconds, which ends
+    /// this long after the onset. Not used with a transition.
+    pub overlap: f64,
+    /// Whether the envelope carries on into the next event instead of
+    /// releasing.
+    pub legato_next: bool,
+}
+
+impl GrainEvent {
+    /// Whether this is a rest, which renders as silence.
+    pub fn is_rest(&self) -> bool {
+        self.source == Phoneme::Space
+    }
+}
+
+/// A fully-resolved, linear plan for grain-based synthesis.
+/// Voice-agnostic.
+#[derive(Clone, Debug)]
+pub struct GrainTimeline {
+    /// Events in the order they start.
+    pub events: Vec<GrainEvent>,
+    /// Pitch offset added to every event in cents, on the output timeline.
+    /// Resolve an [`Automation`] with the score's tempo map to fill it in.
+    ///
+    /// [`Automation`]: automation::Automation
+    pub pitch_bend: AutomationCurve,
+    /// Gain applied to the mix in decibels, on the output timeline.
+    pub dynamics: AutomationCurve,
+}
+
+impl GrainTimeline {
+    /// Renders the timeline. Every event is placed exactly at its onset and
+    /// fills exactly its duration; the output ends with the last event.
+    ///
+    /// Rests are exact silence. A note followed by a rest ends with a short
+    /// fade instead of a transition, and the note after a rest starts at its
+    /// own onset without one.
+    ///
+    /// Each event is scaled by its velocity and envelope, which neither
+    /// releases nor starts over between legato events, and the mix by
+    /// [`dynamics`](Self::dynamics). The mix is then scaled down as a whole if
+    /// it exceeds full scale, so the balance between notes is kept.
+    ///
+    /// # Errors
+    /// Returns an error if a sample can't be loaded or processed.
+    pub fn render(&self, voice: &mut Voice) -> Result<AudioBuffer> {
+        let sample_rate = voice.sample_rate();
+        let to_samples = |secs: f64| (secs.max(0.0) * f64::from(sample_rate)).round() as usize;
+        let declick = to_samples(DECLICK_TIME);
+
+        let out_len = self
+            .events
+            .iter()
+            .map(|e| to_samples(e.start + e.duration))
+            .max()
+            .unwrap_or(0);
+        let mut out = vec![0.0f32; out_len];
+
+        // Length of the crossfade from the previous event into the current one,
+        // the glide from the previous note, and whether the envelope carries on
+        // from the previous event.
+        let mut fade_in = 0usize;
+        let mut glide_in = None;
+        let mut legato_in = false;
+        for (i, event) in self.events.iter().enumerate() {
+            // Rests are left silent and end any transition into them, so the
+            // next note starts afresh.
+            if event.is_rest() {
+                fade_in = 0;
+                glide_in = None;
+                legato_in = false;
+                continue;
+            }
+            let next = self.events.get(i + 1).filter(|next| !next.is_rest());
+            let legato_out = event.legato_next && next.is_some();
+            if fade_in == 0 {
+                fade_in = to_samples(event.overlap);
+            }
+            // Length of the crossfade into an overlapping next event.
+            let cut = next.map_or(0, |next| to_samples(next.overlap));
+
+            let onset = to_samples(event.start);
+            let len = to_samples(event.start + event.duration).saturating_sub(onset);
+
+            let fade_out = match (&event.interp, next) {
+                (Some(interp), Some(next)) => {
+                    let next_len = to_samples(next.start + next.duration)
+                        .saturating_sub(to_samples(next.start));
+                    transition_len(voice, event.source, next.source, interp.fade_len)?.min(next_len)
+                }
+                _ => 0,
+            };
+
+            let secs = |samples: usize| samples as f32 / sample_rate as f32;
+            let glide_out = match (&event.interp, event.portamento, next) {
+                (Some(_), Some(options), Some(next)) => Some(Glide::across(
+                    options,
+                    next.note.0 - event.note.0,
+                    secs(len),
+                    secs(fade_out),
+                )),
+                _ => None,
+            };
+            let glides = [glide_in, glide_out.map(|[out, _]| out)];
+            let bend = |t: f32| {
+                glides
+                    .iter()
+                    .flatten()
+                    .map(|g| g.semitones_at(t))
+                    .sum::<f32>()
+                    + self.pitch_bend.value_at(event.start + f64::from(t)) / 100.0
+            };
+
+            let cur = render_event(voice, event, len + fade_out, bend)?;
+
+            for (j, &v) in cur.iter().enumerate() {
+                let level = event.velocity
+                    * event.envelope.map_or(1.0, |envelope| {
+                        envelope.gain_at(
+                            secs(j),
+                            secs(len),
+                            legato_in || fade_in > 0,
+                            legato_out || fade_out > 0,
+                        )
+                    });
+                let gain = if j < fade_in {
+                    smoothstep(j as f32 / fade_in as f32)
+                } else if j >= len {
+                    1.0 - smoothstep((j - len) as f32 / fade_out as f32)
+                } else if fade_out == 0 && cut > 0 && len - j <= cut {
+                    smoothstep((len - j) as f32 / cut as f32)
+                } else if fade_out == 0 && len - j <= declick {
+                    (len - j) as f32 / declick as f32
+                } else {
+                    1.0
+                };
+
+                if let Some(o) = out.get_mut(onset + j) {
+                    *o += v * gain * level;
+                }
+            }
+
+            fade_in = fade_out;
+            legato_in = legato_out;
+            glide_in = glide_out.map(|[_, into]| into);
+        }
+
+        if !self.dynamics.is_empty() {
+            for (i, s) in out.iter_mut().enumerate() {
+                *s *= db_to_gain(self.dynamics.value_at(i as f64 / f64::from(sample_rate)));
+            }
+        }
+        limit_peak(&mut out);
+
+        Ok(AudioBuffer {
+            sample_rate,
+            samples: out,
+        })
+    }
+}
+
+/// Renders a single event to exactly `len` samples, bending the pitch by
+/// `bend(t)` semitones `t` seconds after the onset.
+fn render_event(
+    voice: &mut Voice,
+    event: &GrainEvent,
+    len: usize,
+    bend: impl Fn(f32) -> f32,
+) -> Result<Vec<f32>> {
+    let base_note = if_periodic(event.source, || voice.base_note(event.source).copied())?;
+
+    let marks = match base_note {
+        Some(_) => voice.pitch_marks(event.source)?.to_vec(),
+        None => Vec::new(),
+    };
+    let timing = voice.timing(event.source)?;
+    let analysis = *voice.analysis(event.source);
+
+    let buf = voice.sample(event.source)?;
+
+    // Play the fixed and release regions as recorded and stretch the rest to
+    // fit, unless the note is too short to fit them at all.
+    let (fixed, release) = (timing.fixed, timing.release.max(timing.fixed));
+    let unstretched = fixed + (buf.len() - release);
+    let (fixed, release) = if unstretched < len && release > fixed {
+        (fixed, release)
+    } else {
+        (0, buf.len())
+    };
+    let sample_rate = buf.sample_rate as f32;
+    let time_stretch =
+        (len - (fixed + buf.len() - release)) as f32 / (release - fixed).max(1) as f32;
+    let fixed_end = fixed as f32 / sample_rate;
+    let release_start = fixed_end + (release - fixed) as f32 * time_stretch / sample_rate;
+    let stretch = |t| {
+        if t < fixed_end || t >= release_start {
+            1.0
+        } else {
+            time_stretch
+        }
+    };
+
+    let mut cur = match (base_note, event.source) {
+        (Some(base_note), _) => {
+            let semitone_diff = event.note.0 - base_note.0;
+            let pitch_ratio = 2.0_f32.powf(semitone_diff / 12.0);
+            let vibrato = event.vibrato.as_ref().map(|options| {
+                Vibrato::new(
+                    options,
+                    len as f32 / sample_rate,
+                    event.instance_id.index() as u64,
+                )
+            });
+
+            psola_curve(
+                buf,
+                &analysis,
+                &marks,
+                |t| {
+                    pitch_ratio
+                        * 2.0_f32.powf(bend(t) / 12.0)
+                        * vibrato.as_ref().map_or(1.0, |v| v.ratio_at(t))
+                },
+                stretch,
+                None,
+            )?
+            .samples
+        }
+        // Bursts don't survive stretching; play them as recorded.
+        (None, Phoneme::Consonant(c)) if c.manner() == Manner::Plosive => buf.samples.clone(),
+        (None, _) => stretch_unvoiced(buf, stretch)?.samples,
+    };
+    cur.resize(len, 0.0);
+
+    Ok(cur)
+}
+
+/// Length of a transition of `grains` pitch periods between `from` and `to`,
+/// in samples. Periods are measured on whichever phoneme is periodic, `from`
+/// first; there's no transition between two aperiodic phonemes.
+fn transition_len(voice: &mut Voice, from: Phoneme, to: Phoneme, grains: usize) -> Result<usize> {
+    for phoneme in [from, to] {
+        if let Some(period) = if_periodic(phoneme, || voice.period(phoneme).copied())? {
+            return Ok(grains * period);
+        }
+    }
+    Ok(0)
+}
+
+/// Analyses `phoneme` with `analyse` if it's periodic. Voiced consonants whose
+/// sample has too little voicing to analyse are treated as aperiodic.
+fn if_periodic<T>(phoneme: Phoneme, analyse: impl FnOnce() -> Result<T>) -> Result<Option<T>> {
+    if !phoneme.is_periodic() {
+        return Ok(None);
+    }
+    match analyse() {
+        Ok(value) => Ok(Some(value)),
+        Err(VoxlabError::NoVoicedRegion | VoxlabError::NoPitchMarks)
+            if matches!(phoneme, Phoneme::Consonant(_)) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Seals [`Schedule`].
+trait PrepareSealed {}
+
+/// Turns a sequence of [`PhonemeInstance`]s into a [`GrainTimeline`].
+#[expect(private_bounds, reason = "intended")]
+pub trait Schedule: PrepareSealed {
+    /// Schedules with the default tempo (see [`TempoMap::default`]).
+    fn schedule(&self) -> GrainTimeline {
+        self.schedule_with(&TempoMap::default())
+    }
+
+    /// Schedules, resolving tick-based times through `tempo`. Only the
+    /// preutterance and overlap set on the instances are used.
+    fn schedule_with(&self, tempo: &TempoMap) -> GrainTimeline;
+
+    /// Like [`Schedule::schedule_with`], taking the preutterance and overlap
+    /// of instances that don't set them from `voice`.
+    ///
+    /// # Errors
+    /// Returns an error if a sample's timing can't be loaded.
+    fn schedule_for(&self, tempo: &TempoMap, voice: &mut Voice) -> Result<GrainTimeline>;
+}
+
+impl<T: AsRef<[PhonemeInstance]>> PrepareSealed for T {}
+
+impl<T: AsRef<[PhonemeInstance]>> Schedule for T {
+    fn schedule_with(&self, tempo: &TempoMap) -> GrainTimeline {
+        let phonemes = self.as_ref();
+        let onsets = phonemes
+            .iter()
+            .map(|phoneme| {
+                (
+                    phoneme.options.preutterance.unwrap_or(0.0),
+                    phoneme.options.overlap.unwrap_or(0.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        schedule_instances(phonemes, tempo, &onsets)
+    }
+
+    fn schedule_for(&self, tempo: &TempoMap, voice: &mut Voice) -> Result<GrainTimeline> {
+        let phonemes = self.as_ref();
+        let sample_rate = f64::from(voice.sample_rate());
+        let onsets = phonemes
+            .iter()
+            .map(|phoneme| {
+                let options = &phoneme.options;
+                Ok(match (options.preutterance, options.overlap) {
+                    _ if phoneme.is_rest() => (0.0, 0.0),
+                    (Some(preutterance), Some(overlap)) => (preutterance, overlap),
+                    (preutterance, overlap) => {
+                        let timing = voice.timing(phoneme.phoneme)?;
+                        (
+                            preutterance.unwrap_or(timing.preutterance as f64 / sample_rate),
+                            overlap.unwrap_or(timing.overlap as f64 / sample_rate),
+                        )
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(schedule_instances(phonemes, tempo, &onsets))
+    }
+}
+
+/// Schedules `phonemes`, moving each one's start earlier by the first of its
+/// `onsets` (the preutterance, in seconds) and crossfading it with the
+/// previous event over the second (the overlap).
+fn schedule_instances(
+    phonemes: &[PhonemeInstance],
+    tempo: &TempoMap,
+    onsets: &[(f64, f64)],
+) -> GrainTimeline {
+    let mut out = Vec::<GrainEvent>::new();
+    for (i, phoneme) in phonemes.iter().enumerate() {
+        let next = phonemes.get(i + 1);
+        let (onset, duration) = tempo.span(phoneme.start, phoneme.duration);
+
+        // Start early enough for the preutterance to land on the onset, but
+        // not before the score or the previous event.
+        let (preutterance, overlap) = if phoneme.is_rest() {
+            (0.0, 0.0)
+        } else {
+            onsets[i]
+        };
+        let earliest = out.last().map_or(0.0, |prev| prev.start);
+        let start = (onset - preutterance.max(0.0)).max(earliest).min(onset);
+
+        // Cut the previous event short so it only reaches `overlap` into this
+        // one, or none at all if it transitions into it.
+        let mut overlap_secs = 0.0;
+        if let Some(prev) = out.last_mut() {
+            let overlap = if prev.interp.is_some() {
+                0.0
+            } else {
+                overlap.max(0.0)
+            };
+            let end = prev.start + prev.duration;
+            if start < onset && start + overlap < end {
+                prev.duration = start + overlap - prev.start;
+            }
+            let end = prev.start + prev.duration;
+            if end <= start + overlap {
+                overlap_secs = (end - start).max(0.0);
+            }
+        }
+
+        // There's no transition or legato into or out of a rest.
+        let continues = !phoneme.is_rest() && next.is_some_and(|next| !next.is_rest());
+        let transition = phoneme
+            .options
+            .next_transition
+            .as_ref()
+            .filter(|_| continues);
+
+        out.push(GrainEvent {
+            instance_id: phoneme.instance_id,
+            source: phoneme.phoneme,
+            start,
+            duration: duration + (onset - start),
+            note: phoneme.note,
+            interp: transition.map(|trans| GrainInterp {
+                fade_len: trans.length_grains,
+            }),
+            portamento: transition.and_then(|trans| trans.portamento),
+            vibrato: phoneme.options.vibrato.clone(),
+            velocity: phoneme.velocity,
+            envelope: phoneme.options.envelope,
+            overlap: overlap_secs,
+            legato_next: phoneme.options.legato_next && continues,
+        });
+    }
+
+    GrainTimeline {
+        events: out,
+        pitch_bend: AutomationCurve::default(),
+        dynamics: AutomationCurve::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::phoneme::ipa::Vowel;
+
+    const A: Phoneme = Phoneme::Vowel(Vowel::OpenBackUnrounded);
+
+    /// An instance of `phoneme` at `start` seconds lasting `duration`, at
+    /// middle C.
+    fn instance(
+        index: usize,
+        phoneme: Phoneme,
+        start: f64,
+        duration: f64,
+        options: PhonemeOptions,
+    ) -> PhonemeInstance {
+        PhonemeInstance {
+            instance_id: InstanceId::new(index),
+            phoneme,
+            start: Time::Seconds(start),
+            duration: Time::Seconds(duration),
+            options,
+            note: MidiNote(60.0),
+            velocity: 1.0,
+        }
+    }
+
+    /// Options transitioning into the next phoneme with a glide.
+    fn gliding() -> PhonemeOptions {
+        PhonemeOptions {
+            next_transition: Some(TransitionOptions {
+                length_grains: 4,
+                portamento: Some(PortamentoOptions::default()),
+            }),
+            legato_next: true,
+            ..PhonemeOptions::default()
+        }
+    }
+
+    /// Sings, rests and sings again, each for `len` seconds, every instance
+    /// asking to transition into the next.
+    fn note_rest_note(len: f64) -> Vec<PhonemeInstance> {
+        vec![
+            instance(0, A, 0.0, len, gliding()),
+            instance(1, Phoneme::Space, len, len, gliding()),
+            instance(2, A, 2.0 * len, len, gliding()),
+        ]
+    }
+
+    #[test]
+    fn rests_break_transitions() {
+        let events = note_rest_note(0.5).schedule().events;
+        assert_eq!(events.len(), 3);
+        for event in &events {
+            assert!(event.interp.is_none(), "{event:?}");
+            assert!(event.portamento.is_none(), "{event:?}");
+            assert!(!event.legato_next, "{event:?}");
+            assert!(event.overlap.abs() < 1e-12, "{event:?}");
+        }
+        assert!(events[1].is_rest());
+        assert!((events[1].start - 0.5).abs() < 1e-12);
+        assert!((events[1].duration - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rests_render_as_silence() {
+        let mut voice = Voice::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/samples"),
+            44100,
+            HashMap::new(),
+        );
+        let out = note_rest_note(0.25)
+            .schedule()
+            .render(&mut voice)
+            .expect("render");
+        let quarter = 44100 / 4;
+        assert_eq!(out.len(), 3 * quarter);
+
+        // The rest is exact silence for its whole duration.
+        assert!(out.samples[quarter..2 * quarter].iter().all(|&s| s == 0.0));
+        // The note before it fades out instead of being cut off.
+        let declick = (DECLICK_TIME * 44100.0) as usize;
+        let before = &out.samples[quarter - 4 * declick..quarter - declick];
+        let peak = before.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.01, "note is silent before the rest");
+        assert!(out.samples[quarter - 1].abs() < peak / 10.0);
+        // The note after it starts at its own onset.
+        assert!(
+            out.samples[2 * quarter..2 * quarter + declick]
+                .iter()
+                .any(|&s| s != 0.0)
+        );
+    }
+
+    /// Options with an explicit preutterance and overlap.
+    fn timed(preutterance: f64, overlap: f64) -> PhonemeOptions {
+        PhonemeOptions {
+            preutterance: Some(preutterance),
+            overlap: Some(overlap),
+            ..PhonemeOptions::default()
+        }
+    }
+
+    /// Start, duration and overlap of every event.
+    fn spans(instances: &[PhonemeInstance]) -> Vec<(f64, f64, f64)> {
+        instances
+            .schedule()
+            .events
+            .iter()
+            .map(|event| (event.start, event.duration, event.overlap))
+            .collect()
+    }
+
+    fn assert_spans(actual: &[(f64, f64, f64)], expected: &[(f64, f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-9 && (a.1 - e.1).abs() < 1e-9 && (a.2 - e.2).abs() < 1e-9,
+                "{actual:?} isn't {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn preutterance_moves_the_start_earlier() {
+        let spans = spans(&[
+            instance(0, A, 0.0, 1.0, timed(0.0, 0.0)),
+            instance(1, A, 1.0, 1.0, timed(0.1, 0.03)),
+        ]);
+        // The second event starts 100 ms early and still ends on time; the
+        // first is cut to reach 30 ms into it, which is the crossfade.
+        assert_spans(&spans, &[(0.0, 0.93, 0.0), (0.9, 1.1, 0.03)]);
+    }
+
+    #[test]
+    fn preutterance_stops_at_the_previous_start() {
+        let spans = spans(&[
+            instance(0, A, 0.05, 0.05, timed(0.1, 0.0)),
+            instance(1, A, 0.1, 1.0, timed(0.5, 0.02)),
+        ]);
+        // Neither event can start before the score or the previous event, so
+        // the first is left with just the crossfade into the second.
+        assert_spans(&spans, &[(0.0, 0.02, 0.0), (0.0, 1.1, 0.02)]);
+    }
+
+    #[test]
+    fn overlap_is_limited_by_the_previous_event() {
+        let spans = spans(&[
+            instance(0, A, 0.0, 0.5, timed(0.0, 0.0)),
+            instance(1, A, 1.0, 1.0, timed(0.1, 0.2)),
+        ]);
+        // The first event ends before the second's overlap would, so it isn't
+        // cut and the crossfade is only as long as they actually overlap.
+        assert_spans(&spans, &[(0.0, 0.5, 0.0), (0.9, 1.1, 0.0)]);
+    }
+
+    #[test]
+    fn transitions_replace_the_overlap() {
+        let mut first = timed(0.0, 0.0);
+        first.next_transition = gliding().next_transition;
+        let events = [
+            instance(0, A, 0.0, 1.0, first),
+            instance(1, A, 1.0, 1.0, timed(0.1, 0.03)),
+        ];
+        // Cut exactly where the second event starts, with no crossfade.
+        assert_spans(&spans(&events), &[(0.0, 0.9, 0.0), (0.9, 1.1, 0.0)]);
+        assert!(events.schedule().events[0].interp.is_some());
+    }
+}
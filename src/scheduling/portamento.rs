@@ -0,0 +1,200 @@
+//! Pitch glides between consecutive notes.
+
+use std::f32::consts::PI;
+
+use crate::nice::smoothstep;
+
+/// How the pitch moves from one note to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PortamentoShape {
+    /// At a constant rate.
+    Linear,
+    /// Slowly at first and at the end, quickest in the middle.
+    #[default]
+    Sigmoid,
+    /// Like [`PortamentoShape::Sigmoid`], but going past the target and
+    /// settling back onto it.
+    Overshoot {
+        /// How far past the target the pitch goes, as a fraction of the
+        /// interval.
+        amount: f32,
+    },
+    /// Like [`PortamentoShape::Sigmoid`], but first moving away from the
+    /// target, as if winding up for the jump.
+    Preparation {
+        /// How far the pitch moves away, as a fraction of the interval.
+        amount: f32,
+    },
+}
+
+impl PortamentoShape {
+    /// Fraction of the interval covered at `x`, the fraction of the glide's
+    /// length elapsed. 0 at the start and 1 at the end; overshoot and
+    /// preparation leave `0..=1` in between.
+    pub fn progress(self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => x,
+            Self::Sigmoid => smoothstep(x),
+            // The bumps are zero at both ends, peaking about 70% of the way
+            // through for overshoot and 30% for preparation.
+            Self::Overshoot { amount } => smoothstep(x) + amount * (PI * x * x).sin(),
+            Self::Preparation { amount } => {
+                smoothstep(x) - amount * (PI * (1.0 - x) * (1.0 - x)).sin()
+            }
+        }
+    }
+}
+
+/// A legato pitch glide into the next note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortamentoOptions {
+    /// Length of the glide in seconds. It's centred on the middle of the
+    /// crossfade between the notes.
+    pub length: f32,
+    /// How the pitch moves.
+    pub shape: PortamentoShape,
+}
+
+impl Default for PortamentoOptions {
+    /// A 120 ms sigmoid glide.
+    fn default() -> Self {
+        Self {
+            length: 0.12,
+            shape: PortamentoShape::default(),
+        }
+    }
+}
+
+/// A glide placed on the timeline of one event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glide {
+    /// Middle of the glide, in seconds from the onset of the event.
+    pub centre: f32,
+    /// Offset from the event's note before the glide, in semitones.
+    pub from: f32,
+    /// Offset from the event's note after the glide, in semitones.
+    pub to: f32,
+    /// Length and shape of the glide.
+    pub options: PortamentoOptions,
+}
+
+impl Glide {
+    /// The glide out of an event lasting `len` seconds into the next one,
+    /// `interval` semitones away, and the same glide on the next event's
+    /// timeline. Both are centred on the middle of the `fade` seconds the
+    /// events crossfade over, so they agree on the pitch throughout it.
+    pub fn across(options: PortamentoOptions, interval: f32, len: f32, fade: f32) -> [Self; 2] {
+        [
+            Self {
+                centre: len + fade / 2.0,
+                from: 0.0,
+                to: interval,
+                options,
+            },
+            Self {
+                centre: fade / 2.0,
+                from: -interval,
+                to: 0.0,
+                options,
+            },
+        ]
+    }
+
+    /// Offset from the event's note `t` seconds after its onset, in
+    /// semitones.
+    pub fn semitones_at(&self, t: f32) -> f32 {
+        let x = if self.options.length > 0.0 {
+            (t - self.centre) / self.options.length + 0.5
+        } else if t < self.centre {
+            0.0
+        } else {
+            1.0
+        };
+        self.from + (self.to - self.from) * self.options.shape.progress(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPES: [PortamentoShape; 4] = [
+        PortamentoShape::Linear,
+        PortamentoShape::Sigmoid,
+        PortamentoShape::Overshoot { amount: 0.15 },
+        PortamentoShape::Preparation { amount: 0.15 },
+    ];
+
+    #[test]
+    fn shapes_start_and_end_on_the_notes() {
+        for shape in SHAPES {
+            assert!(shape.progress(-1.0).abs() < 1e-6, "{shape:?}");
+            assert!(shape.progress(0.0).abs() < 1e-6, "{shape:?}");
+            assert!((shape.progress(1.0) - 1.0).abs() < 1e-6, "{shape:?}");
+            assert!((shape.progress(2.0) - 1.0).abs() < 1e-6, "{shape:?}");
+        }
+        assert!((PortamentoShape::Linear.progress(0.25) - 0.25).abs() < 1e-6);
+        assert!((PortamentoShape::Sigmoid.progress(0.5) - 0.5).abs() < 1e-6);
+        assert!(PortamentoShape::Overshoot { amount: 0.15 }.progress(0.85) > 1.0);
+        assert!(PortamentoShape::Preparation { amount: 0.15 }.progress(0.15) < 0.0);
+    }
+
+    #[test]
+    fn glide_is_centred() {
+        let glide = Glide {
+            centre: 1.0,
+            from: 0.0,
+            to: 4.0,
+            options: PortamentoOptions {
+                length: 0.2,
+                shape: PortamentoShape::Linear,
+            },
+        };
+        assert!(glide.semitones_at(0.85).abs() < 1e-6);
+        assert!((glide.semitones_at(0.95) - 1.0).abs() < 1e-5);
+        assert!((glide.semitones_at(1.0) - 2.0).abs() < 1e-5);
+        assert!((glide.semitones_at(1.2) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_length_jumps_at_the_centre() {
+        let glide = Glide {
+            centre: 0.5,
+            from: 0.0,
+            to: -3.0,
+            options: PortamentoOptions {
+                length: 0.0,
+                shape: PortamentoShape::Sigmoid,
+            },
+        };
+        assert!(glide.semitones_at(0.49).abs() < 1e-6);
+        assert!((glide.semitones_at(0.5) + 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn glides_agree_across_the_boundary() {
+        let (len, fade, from_note, to_note) = (1.0, 0.1, 60.0, 64.0);
+        for shape in SHAPES {
+            let options = PortamentoOptions {
+                length: 0.12,
+                shape,
+            };
+            let [out, into] = Glide::across(options, to_note - from_note, len, fade);
+            for i in 0..=20 {
+                let t = i as f32 * fade / 20.0;
+                let before = from_note + out.semitones_at(len + t);
+                let after = to_note + into.semitones_at(t);
+                assert!((before - after).abs() < 1e-4, "{shape:?} at {t}");
+            }
+        }
+
+        // Halfway through the crossfade, halfway between the notes.
+        let options = PortamentoOptions::default();
+        let [out, into] = Glide::across(options, 4.0, len, fade);
+        assert!((out.semitones_at(len + fade / 2.0) - 2.0).abs() < 1e-5);
+        assert!((into.semitones_at(fade / 2.0) + 2.0).abs() < 1e-5);
+        assert!(out.semitones_at(0.0).abs() < 1e-6);
+        assert!(into.semitones_at(1.0).abs() < 1e-6);
+    }
+}
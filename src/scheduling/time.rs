@@ -0,0 +1,206 @@
+//! Score timing.
+
+/// A position or length on the score timeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Time {
+    /// Absolute time in seconds.
+    Seconds(f64),
+    /// Tempo-relative MIDI ticks, resolved through a [`TempoMap`].
+    Ticks(u32),
+}
+
+/// A tempo change at a specific tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoChange {
+    /// Tick the new tempo starts at.
+    pub tick: u32,
+    /// Tempo in quarter notes per minute.
+    pub bpm: f64,
+}
+
+/// Maps tempo-relative ticks to seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    /// Ticks per quarter note.
+    ticks_per_quarter: u16,
+    /// Sorted by tick, always starts at tick 0.
+    changes: Vec<TempoChange>,
+}
+
+impl Default for TempoMap {
+    /// 120 BPM at 480 ticks per quarter note.
+    fn default() -> Self {
+        Self::constant(480, 120.0)
+    }
+}
+
+impl TempoMap {
+    /// A tempo map with a single tempo throughout.
+    ///
+    /// # Panics
+    /// Panics if `ticks_per_quarter` or `bpm` isn't positive.
+    pub fn constant(ticks_per_quarter: u16, bpm: f64) -> Self {
+        assert!(ticks_per_quarter > 0);
+        assert!(bpm > 0.0);
+        Self {
+            ticks_per_quarter,
+            changes: vec![TempoChange { tick: 0, bpm }],
+        }
+    }
+
+    /// Number of ticks per quarter note.
+    pub fn ticks_per_quarter(&self) -> u16 {
+        self.ticks_per_quarter
+    }
+
+    /// Tempo changes, sorted by tick.
+    pub fn changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    /// Sets the tempo from `tick` onwards, until the next change. A change at
+    /// an existing tick replaces it.
+    ///
+    /// # Panics
+    /// Panics if `bpm` isn't positive.
+    pub fn insert(&mut self, tick: u32, bpm: f64) {
+        assert!(bpm > 0.0);
+        let i = self.changes.partition_point(|c| c.tick < tick);
+        if let Some(change) = self.changes.get_mut(i)
+            && change.tick == tick
+        {
+            change.bpm = bpm;
+        } else {
+            self.changes.insert(i, TempoChange { tick, bpm });
+        }
+    }
+
+    /// Length of one tick at `bpm`, in seconds.
+    fn seconds_per_tick(&self, bpm: f64) -> f64 {
+        60.0 / (bpm * f64::from(self.ticks_per_quarter))
+    }
+
+    /// Converts an absolute tick to seconds.
+    pub fn tick_to_seconds(&self, tick: u32) -> f64 {
+        let mut secs = 0.0;
+        for (i, change) in self.changes.iter().enumerate() {
+            if change.tick >= tick {
+                break;
+            }
+            let end = self
+                .changes
+                .get(i + 1)
+                .map_or(tick, |next| next.tick.min(tick));
+            secs += f64::from(end - change.tick) * self.seconds_per_tick(change.bpm);
+        }
+        secs
+    }
+
+    /// Converts seconds to an absolute (fractional) tick.
+    pub fn seconds_to_ticks(&self, seconds: f64) -> f64 {
+        let mut secs = 0.0;
+        for (i, change) in self.changes.iter().enumerate() {
+            let per_tick = self.seconds_per_tick(change.bpm);
+            if let Some(next) = self.changes.get(i + 1) {
+                let span = f64::from(next.tick - change.tick) * per_tick;
+                if secs + span < seconds {
+                    secs += span;
+                    continue;
+                }
+            }
+            return f64::from(change.tick) + (seconds - secs) / per_tick;
+        }
+        0.0
+    }
+
+    /// Resolves an absolute position to seconds.
+    pub fn seconds(&self, time: Time) -> f64 {
+        match time {
+            Time::Seconds(secs) => secs,
+            Time::Ticks(tick) => self.tick_to_seconds(tick),
+        }
+    }
+
+    /// Resolves a span starting at `start` and lasting `duration` to a start
+    /// and duration in seconds. Tick durations follow any tempo changes inside
+    /// the span.
+    pub fn span(&self, start: Time, duration: Time) -> (f64, f64) {
+        let start_secs = self.seconds(start);
+        let duration_secs = match (start, duration) {
+            (_, Time::Seconds(secs)) => secs,
+            (Time::Ticks(start), Time::Ticks(len)) => {
+                self.tick_to_seconds(start + len) - start_secs
+            }
+            (Time::Seconds(start), Time::Ticks(len)) => {
+                let start_tick = self.seconds_to_ticks(start);
+                let end_tick = start_tick + f64::from(len);
+                self.fractional_tick_to_seconds(end_tick) - start_secs
+            }
+        };
+        (start_secs, duration_secs)
+    }
+
+    /// Converts a fractional tick to seconds.
+    fn fractional_tick_to_seconds(&self, tick: f64) -> f64 {
+        let whole = tick.floor();
+        let base = self.tick_to_seconds(whole as u32);
+        let i = self.changes.partition_point(|c| f64::from(c.tick) <= whole);
+        base + (tick - whole) * self.seconds_per_tick(self.changes[i - 1].bpm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 120 BPM, dropping to 60 BPM after a quarter note.
+    fn slowing() -> TempoMap {
+        let mut tempo = TempoMap::default();
+        tempo.insert(480, 60.0);
+        tempo
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn constant_tempo() {
+        let tempo = TempoMap::default();
+        assert_close(tempo.tick_to_seconds(0), 0.0);
+        assert_close(tempo.tick_to_seconds(480), 0.5);
+        assert_close(tempo.seconds_to_ticks(1.0), 960.0);
+    }
+
+    #[test]
+    fn tempo_changes() {
+        let tempo = slowing();
+        assert_close(tempo.tick_to_seconds(240), 0.25);
+        assert_close(tempo.tick_to_seconds(960), 1.5);
+        assert_close(tempo.seconds_to_ticks(1.5), 960.0);
+        assert_close(tempo.seconds(Time::Seconds(0.75)), 0.75);
+    }
+
+    #[test]
+    fn insert_replaces_existing_change() {
+        let mut tempo = slowing();
+        tempo.insert(480, 240.0);
+        assert_eq!(tempo.changes().len(), 2);
+        assert_close(tempo.tick_to_seconds(960), 0.75);
+    }
+
+    #[test]
+    fn spans_follow_tempo_changes() {
+        let tempo = slowing();
+        let (start, duration) = tempo.span(Time::Ticks(240), Time::Ticks(480));
+        assert_close(start, 0.25);
+        assert_close(duration, 0.75);
+
+        let (start, duration) = tempo.span(Time::Seconds(0.25), Time::Ticks(480));
+        assert_close(start, 0.25);
+        assert_close(duration, 0.75);
+
+        let (_, duration) = tempo.span(Time::Ticks(240), Time::Seconds(0.1));
+        assert_close(duration, 0.1);
+    }
+}
@@ -0,0 +1,162 @@
+//! Vibrato.
+
+use std::f32::consts::TAU;
+
+use crate::nice::{Rng, smoothstep};
+
+/// Largest jitter, so that no cycle is less than half as long as nominal.
+const MAX_JITTER: f32 = 0.5;
+
+/// Most cycles laid out over one note, so absurd rates can't exhaust memory.
+const MAX_CYCLES: usize = 10_000;
+
+/// Periodic pitch modulation of a note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VibratoOptions {
+    /// Oscillations per second.
+    pub rate: f32,
+    /// Largest deviation from the note, in cents.
+    pub depth: f32,
+    /// Time from the onset of the note until the vibrato starts, in seconds.
+    pub delay: f32,
+    /// Time the vibrato takes to reach its full depth once it starts, in
+    /// seconds.
+    pub fade_in: f32,
+    /// How much the rate and depth of each cycle vary at random, as a
+    /// fraction of their nominal values, up to 0.5. Zero gives a perfectly
+    /// regular vibrato.
+    pub jitter: f32,
+    /// Seed of the jitter. Each note mixes in its own instance ID, so notes
+    /// sharing options still vary differently.
+    pub seed: u64,
+}
+
+impl Default for VibratoOptions {
+    /// 5.5 Hz, ±40 cents, starting after 200 ms and fading in over 300 ms,
+    /// with 10% jitter.
+    fn default() -> Self {
+        Self {
+            rate: 5.5,
+            depth: 40.0,
+            delay: 0.2,
+            fade_in: 0.3,
+            jitter: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// A vibrato laid out over a note, with the jitter of every cycle decided.
+#[derive(Clone, Debug)]
+pub struct Vibrato {
+    /// Time the vibrato starts, in seconds from the onset.
+    delay: f32,
+    /// Fade-in time in seconds.
+    fade_in: f32,
+    /// Start time, period and depth of each cycle, in seconds, seconds and
+    /// cents.
+    cycles: Vec<(f32, f32, f32)>,
+}
+
+impl Vibrato {
+    /// Lays out `options` over a note lasting `duration` seconds, mixing
+    /// `salt` into the jitter seed.
+    pub fn new(options: &VibratoOptions, duration: f32, salt: u64) -> Self {
+        let mut rng = Rng::new(options.seed ^ salt);
+        let jitter = options.jitter.clamp(0.0, MAX_JITTER);
+        let mut vary = || 1.0 + jitter * (2.0 * rng.next_f32() - 1.0);
+
+        let mut cycles = Vec::new();
+        if options.rate > 0.0 && options.rate.is_finite() {
+            let mut start = options.delay.max(0.0);
+            while start < duration && cycles.len() < MAX_CYCLES {
+                let period = options.rate.recip() * vary();
+                cycles.push((start, period, options.depth * vary()));
+                start += period;
+            }
+        }
+
+        Self {
+            delay: options.delay.max(0.0),
+            fade_in: options.fade_in.max(0.0),
+            cycles,
+        }
+    }
+
+    /// Pitch deviation `t` seconds after the onset, in cents.
+    pub fn cents_at(&self, t: f32) -> f32 {
+        let i = self.cycles.partition_point(|&(start, _, _)| start <= t);
+        let Some(&(start, period, depth)) = i.checked_sub(1).and_then(|i| self.cycles.get(i))
+        else {
+            return 0.0;
+        };
+        let envelope = if self.fade_in > 0.0 {
+            smoothstep((t - self.delay) / self.fade_in)
+        } else {
+            1.0
+        };
+        // Cycles start and end on a zero crossing, so changing the period and
+        // depth between them doesn't make the pitch jump.
+        depth * envelope * (TAU * ((t - start) / period).min(1.0)).sin()
+    }
+
+    /// Pitch ratio `t` seconds after the onset.
+    pub fn ratio_at(&self, t: f32) -> f32 {
+        2.0_f32.powf(self.cents_at(t) / 1200.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regular_vibrato() {
+        let options = VibratoOptions {
+            rate: 5.0,
+            depth: 50.0,
+            delay: 0.0,
+            fade_in: 0.0,
+            jitter: 0.0,
+            seed: 0,
+        };
+        let vibrato = Vibrato::new(&options, 1.0, 0);
+        assert_eq!(vibrato.cycles.len(), 5);
+        assert!((vibrato.cents_at(0.05) - 50.0).abs() < 1e-3);
+        assert!((vibrato.cents_at(0.15) + 50.0).abs() < 1e-3);
+        assert!((vibrato.ratio_at(0.05) - 2.0_f32.powf(50.0 / 1200.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn silent_before_delay() {
+        let vibrato = Vibrato::new(&VibratoOptions::default(), 1.0, 0);
+        assert_eq!(vibrato.cents_at(0.1).to_bits(), 0.0f32.to_bits());
+    }
+
+    #[test]
+    fn extreme_jitter_stays_finite() {
+        let options = VibratoOptions {
+            jitter: 1.0,
+            ..VibratoOptions::default()
+        };
+        for salt in 0..20 {
+            let vibrato = Vibrato::new(&options, 2.0, salt);
+            assert!(
+                vibrato
+                    .cycles
+                    .iter()
+                    .all(|&(_, period, _)| period >= 0.5 / options.rate)
+            );
+            assert!((0..200).all(|i| vibrato.cents_at(i as f32 * 0.01).is_finite()));
+        }
+    }
+
+    #[test]
+    fn cycles_are_capped() {
+        let options = VibratoOptions {
+            rate: 1e9,
+            ..VibratoOptions::default()
+        };
+        assert_eq!(Vibrato::new(&options, 10.0, 0).cycles.len(), MAX_CYCLES);
+    }
+}
@@ -0,0 +1,752 @@
+//! Standard MIDI File (type 0 and 1) import.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+};
+
+use crate::{
+    audio::MidiNote,
+    phoneme::{
+        ipa::{Phoneme, Vowel},
+        parse::parse_ipa,
+    },
+    scheduling::{
+        PhonemeInstance, PhonemeOptions, TransitionOptions,
+        automation::{Automation, ControlPoint, Interpolation},
+        dynamics::AdsrOptions,
+        time::{TempoMap, Time},
+        vibrato::VibratoOptions,
+    },
+    score::note::{LoweringOptions, Note, lower_notes},
+};
+
+/// Lowest expression level imported, as a fraction of the highest. Keeps an
+/// expression of zero from turning into minus infinity decibels.
+const MIN_EXPRESSION: f32 = 1e-5;
+
+/// Errors produced while importing a MIDI file.
+#[derive(Debug)]
+pub enum MidiError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The file isn't a valid Standard MIDI File.
+    Malformed {
+        /// Byte offset the problem was found at.
+        offset: usize,
+        /// What was wrong.
+        reason: &'static str,
+    },
+    /// The file uses a feature that isn't supported (type 2 files, SMPTE
+    /// time division).
+    Unsupported(&'static str),
+    /// A lyric event wasn't valid IPA.
+    UnknownLyric {
+        /// Tick the lyric is at.
+        tick: u32,
+        /// The lyric text.
+        text: String,
+    },
+}
+
+impl Display for MidiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiError::Io(err) => write!(f, "failed to read MIDI file: {err}"),
+            MidiError::Malformed { offset, reason } => {
+                write!(f, "malformed MIDI file at byte {offset}: {reason}")
+            }
+            MidiError::Unsupported(what) => write!(f, "unsupported MIDI file: {what}"),
+            MidiError::UnknownLyric { tick, text } => {
+                write!(f, "lyric {text:?} at tick {tick} is not valid IPA")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MidiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MidiError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MidiError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Options for [`import_midi`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiImportOptions {
+    /// Only import notes from this track. All tracks are merged if `None`.
+    pub track: Option<usize>,
+    /// Phoneme sung on notes without a lyric.
+    pub default_phoneme: Phoneme,
+    /// Transition applied between consecutive notes.
+    pub transition: Option<TransitionOptions>,
+    /// Vibrato applied to every note.
+    pub vibrato: Option<VibratoOptions>,
+    /// Amplitude envelope applied to every note.
+    pub envelope: Option<AdsrOptions>,
+    /// How the phonemes of each lyric are spread over its note.
+    pub lowering: LoweringOptions,
+    /// Pitch offset of a fully deflected pitch wheel, in cents.
+    pub bend_range: f32,
+}
+
+impl Default for MidiImportOptions {
+    fn default() -> Self {
+        Self {
+            track: None,
+            default_phoneme: Phoneme::Vowel(Vowel::OpenBackUnrounded),
+            transition: None,
+            vibrato: None,
+            envelope: None,
+            lowering: LoweringOptions::default(),
+            bend_range: 200.0,
+        }
+    }
+}
+
+/// A score imported from a MIDI file. Note times are in ticks; the instances
+/// they're lowered to are in seconds. Automation should be resolved with
+/// [`tempo`](Self::tempo).
+#[derive(Clone, Debug)]
+pub struct MidiScore {
+    /// The file's tempo map.
+    pub tempo: TempoMap,
+    /// One note per note-on, sorted by onset.
+    pub notes: Vec<Note>,
+    /// The notes' phonemes, in order.
+    pub instances: Vec<PhonemeInstance>,
+    /// Pitch wheel movements of the channels the notes are on in cents, as
+    /// steps.
+    pub pitch_bend: Automation,
+    /// Expression controller (CC 11) movements of the channels the notes are
+    /// on in decibels, as steps.
+    pub dynamics: Automation,
+}
+
+/// Imports a Standard MIDI File.
+///
+/// # Errors
+/// Returns an error if the file can't be read or parsed (see [`parse_midi`]).
+pub fn import_midi(
+    path: impl AsRef<Path>,
+    options: &MidiImportOptions,
+) -> Result<MidiScore, MidiError> {
+    parse_midi(&std::fs::read(path)?, options)
+}
+
+/// Parses a Standard MIDI File from memory. Note-on/note-off pairs become
+/// [`Note`]s, and notes that are never released end with their track. Lyric
+/// meta-events (`FF 05`) at a note's onset on the same track give its syllable
+/// in IPA and tempo meta-events (`FF 51`) build the tempo map. Note
+/// velocities, and the pitch wheel and expression controller of the channels
+/// the notes are on, set the loudness and pitch of the notes. The notes are
+/// then lowered to phonemes with [`lower_notes`].
+///
+/// # Errors
+/// Returns an error if the data is malformed, uses SMPTE timing, or has a
+/// lyric that isn't valid IPA.
+pub fn parse_midi(data: &[u8], options: &MidiImportOptions) -> Result<MidiScore, MidiError> {
+    let mut reader = Reader { data, pos: 0 };
+
+    let (format, track_count, division) = reader.header()?;
+    if format > 1 {
+        return Err(MidiError::Unsupported("type 2 files"));
+    }
+    if division & 0x8000 != 0 {
+        return Err(MidiError::Unsupported("SMPTE time division"));
+    }
+    if division == 0 {
+        return Err(reader.malformed("zero ticks per quarter note"));
+    }
+
+    let mut tempo = TempoMap::constant(division, 120.0);
+    // (track, start tick, end tick, key, velocity)
+    let mut notes = Vec::new();
+    // Lyrics per (track, tick), so each attaches to its own track's notes.
+    let mut lyrics = HashMap::new();
+    // Channels the notes are on, and the control changes of every channel, so
+    // that other instruments' controllers don't affect the voice.
+    let mut note_channels = HashSet::new();
+    let mut bends = Vec::new();
+    let mut expressions = Vec::new();
+
+    for track in 0..usize::from(track_count) {
+        let events = reader.track()?;
+        let end = events.last().map_or(0, |event| event.tick);
+        let wanted = options.track.is_none_or(|t| t == track);
+
+        // Onset ticks and velocities of held notes, per (channel, key).
+        let mut held: HashMap<(u8, u8), Vec<(u32, u8)>> = HashMap::new();
+        for event in events {
+            match event.kind {
+                EventKind::NoteOn {
+                    channel,
+                    key,
+                    velocity,
+                } if wanted => {
+                    note_channels.insert(channel);
+                    held.entry((channel, key))
+                        .or_default()
+                        .push((event.tick, velocity));
+                }
+                EventKind::NoteOff { channel, key } if wanted => {
+                    if let Some(starts) = held.get_mut(&(channel, key))
+                        && !starts.is_empty()
+                    {
+                        let (start, velocity) = starts.remove(0);
+                        notes.push((track, start, event.tick, key, velocity));
+                    }
+                }
+                EventKind::PitchBend { channel, value } if wanted => {
+                    bends.push((
+                        channel,
+                        ControlPoint {
+                            time: Time::Ticks(event.tick),
+                            value: f32::from(value) / 8192.0 * options.bend_range,
+                            interpolation: Interpolation::Step,
+                        },
+                    ));
+                }
+                EventKind::Expression { channel, value } if wanted => {
+                    expressions.push((
+                        channel,
+                        ControlPoint {
+                            time: Time::Ticks(event.tick),
+                            value: 20.0 * (f32::from(value) / 127.0).max(MIN_EXPRESSION).log10(),
+                            interpolation: Interpolation::Step,
+                        },
+                    ));
+                }
+                EventKind::Lyric(text) if wanted => {
+                    lyrics.insert((track, event.tick), text);
+                }
+                EventKind::Tempo(micros) => {
+                    tempo.insert(event.tick, 60_000_000.0 / f64::from(micros.max(1)));
+                }
+                _ => {}
+            }
+        }
+
+        // Notes that are never released end with their track.
+        for ((_, key), starts) in held {
+            for (start, velocity) in starts {
+                notes.push((track, start, end, key, velocity));
+            }
+        }
+    }
+
+    notes.sort_by_key(|&(track, start, _, key, _)| (start, key, track));
+    let lane = |points: Vec<(u8, ControlPoint)>| Automation {
+        points: points
+            .into_iter()
+            .filter(|(channel, _)| note_channels.contains(channel))
+            .map(|(_, point)| point)
+            .collect(),
+    };
+    let mut pitch_bend = lane(bends);
+    let mut dynamics = lane(expressions);
+    // The wheel is centred and expression is full until they first move.
+    for lane in [&mut pitch_bend, &mut dynamics] {
+        if lane.points.is_empty() {
+            continue;
+        }
+        lane.points.insert(
+            0,
+            ControlPoint {
+                time: Time::Ticks(0),
+                value: 0.0,
+                interpolation: Interpolation::Step,
+            },
+        );
+    }
+
+    let notes = notes
+        .into_iter()
+        .map(|(track, start, end, key, velocity)| {
+            let phonemes = match lyrics.get(&(track, start)) {
+                Some(text) => parse_ipa(text).map_err(|_| MidiError::UnknownLyric {
+                    tick: start,
+                    text: text.clone(),
+                })?,
+                None => vec![options.default_phoneme],
+            };
+
+            Ok(Note {
+                phonemes,
+                pitch: MidiNote(f32::from(key)),
+                start: Time::Ticks(start),
+                duration: Time::Ticks(end - start),
+                velocity: f32::from(velocity) / 127.0,
+                options: PhonemeOptions {
+                    next_transition: options.transition.clone(),
+                    vibrato: options.vibrato.clone(),
+                    envelope: options.envelope,
+                    preutterance: None,
+                    overlap: None,
+                    legato_next: false,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, MidiError>>()?;
+    let instances = lower_notes(&notes, &tempo, &options.lowering);
+
+    Ok(MidiScore {
+        tempo,
+        notes,
+        instances,
+        pitch_bend,
+        dynamics,
+    })
+}
+
+/// A track event relevant to importing.
+#[derive(Clone, Debug, PartialEq)]
+struct Event {
+    /// Absolute tick of the event.
+    tick: u32,
+    /// What happened.
+    kind: EventKind,
+}
+
+/// The kinds of event the importer cares about.
+#[derive(Clone, Debug, PartialEq)]
+enum EventKind {
+    /// A note started.
+    NoteOn {
+        /// MIDI channel.
+        channel: u8,
+        /// MIDI key number.
+        key: u8,
+        /// Key velocity, from 1 to 127.
+        velocity: u8,
+    },
+    /// A note ended. Also produced by note-ons with a velocity of zero.
+    NoteOff {
+        /// MIDI channel.
+        channel: u8,
+        /// MIDI key number.
+        key: u8,
+    },
+    /// Expression controller (CC 11) moved.
+    Expression {
+        /// MIDI channel.
+        channel: u8,
+        /// Expression level, from 0 to 127.
+        value: u8,
+    },
+    /// Pitch wheel moved.
+    PitchBend {
+        /// MIDI channel.
+        channel: u8,
+        /// Wheel position, from -8192 to 8191.
+        value: i16,
+    },
+    /// Lyric meta-event.
+    Lyric(String),
+    /// Set tempo meta-event, in microseconds per quarter note.
+    Tempo(u32),
+    /// End of track meta-event.
+    EndOfTrack,
+    /// Anything else.
+    Other,
+}
+
+/// Cursor over the raw file.
+struct Reader<'a> {
+    /// The whole file.
+    data: &'a [u8],
+    /// Current byte offset.
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// A [`MidiError::Malformed`] at the current position.
+    fn malformed(&self, reason: &'static str) -> MidiError {
+        MidiError::Malformed {
+            offset: self.pos,
+            reason,
+        }
+    }
+
+    /// Reads `len` bytes.
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], MidiError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| self.malformed("unexpected end of file"))?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    /// Reads a single byte.
+    fn u8(&mut self) -> Result<u8, MidiError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Reads a big-endian `u16`.
+    fn u16(&mut self) -> Result<u16, MidiError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Reads a big-endian `u32`.
+    fn u32(&mut self) -> Result<u32, MidiError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Reads a variable-length quantity.
+    fn vlq(&mut self) -> Result<u32, MidiError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let b = self.u8()?;
+            value = (value << 7) | u32::from(b & 0x7f);
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.malformed("variable-length quantity longer than 4 bytes"))
+    }
+
+    /// Reads the header chunk, returning the format, track count and time
+    /// division.
+    fn header(&mut self) -> Result<(u16, u16, u16), MidiError> {
+        if self.bytes(4)? != b"MThd" {
+            return Err(self.malformed("missing MThd header"));
+        }
+        let len = self.u32()? as usize;
+        if len < 6 {
+            return Err(self.malformed("header chunk too short"));
+        }
+        let format = self.u16()?;
+        let tracks = self.u16()?;
+        let division = self.u16()?;
+        self.bytes(len - 6)?;
+        Ok((format, tracks, division))
+    }
+
+    /// Reads the next track chunk, skipping unknown chunks.
+    fn track(&mut self) -> Result<Vec<Event>, MidiError> {
+        loop {
+            let id = self.bytes(4)?;
+            let len = self.u32()? as usize;
+            let chunk = self.bytes(len)?;
+            if id == b"MTrk" {
+                let mut track = Reader {
+                    data: &self.data[..self.pos],
+                    pos: self.pos - chunk.len(),
+                };
+                return track.events();
+            }
+        }
+    }
+
+    /// Reads events until the end of the track.
+    fn events(&mut self) -> Result<Vec<Event>, MidiError> {
+        let mut events = Vec::new();
+        let mut tick = 0u32;
+        let mut running_status = None;
+
+        while self.pos < self.data.len() {
+            tick = tick.saturating_add(self.vlq()?);
+
+            let mut status = self.u8()?;
+            if status < 0x80 {
+                // Running status: this byte is the first data byte.
+                status =
+                    running_status.ok_or_else(|| self.malformed("data byte without status"))?;
+                self.pos -= 1;
+            }
+
+            let kind = match status {
+                0xff => {
+                    running_status = None;
+                    let ty = self.u8()?;
+                    let len = self.vlq()? as usize;
+                    let data = self.bytes(len)?;
+                    match ty {
+                        0x05 => EventKind::Lyric(String::from_utf8_lossy(data).into_owned()),
+                        0x51 if len == 3 => EventKind::Tempo(
+                            (u32::from(data[0]) << 16)
+                                | (u32::from(data[1]) << 8)
+                                | u32::from(data[2]),
+                        ),
+                        0x2f => {
+                            events.push(Event {
+                                tick,
+                                kind: EventKind::EndOfTrack,
+                            });
+                            break;
+                        }
+                        _ => EventKind::Other,
+                    }
+                }
+                0xf0 | 0xf7 => {
+                    running_status = None;
+                    let len = self.vlq()? as usize;
+                    self.bytes(len)?;
+                    EventKind::Other
+                }
+                0x80..=0xef => {
+                    running_status = Some(status);
+                    let channel = status & 0x0f;
+                    match status & 0xf0 {
+                        0x80 => {
+                            let key = self.u8()?;
+                            self.u8()?;
+                            EventKind::NoteOff { channel, key }
+                        }
+                        0x90 => {
+                            let key = self.u8()?;
+                            match self.u8()? {
+                                0 => EventKind::NoteOff { channel, key },
+                                velocity => EventKind::NoteOn {
+                                    channel,
+                                    key,
+                                    velocity,
+                                },
+                            }
+                        }
+                        0xb0 => {
+                            let controller = self.u8()?;
+                            let value = self.u8()?;
+                            if controller == 11 {
+                                EventKind::Expression { channel, value }
+                            } else {
+                                EventKind::Other
+                            }
+                        }
+                        0xe0 => {
+                            let low = self.u8()?;
+                            let high = self.u8()?;
+                            let value = (u16::from(high & 0x7f) << 7) | u16::from(low & 0x7f);
+                            EventKind::PitchBend {
+                                channel,
+                                value: value.cast_signed() - 8192,
+                            }
+                        }
+                        0xc0 | 0xd0 => {
+                            self.u8()?;
+                            EventKind::Other
+                        }
+                        _ => {
+                            self.bytes(2)?;
+                            EventKind::Other
+                        }
+                    }
+                }
+                _ => return Err(self.malformed("unexpected system message")),
+            };
+
+            events.push(Event { tick, kind });
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A type 1 file with `tracks`, each given as its raw events.
+    fn smf(division: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut data = b"MThd".to_vec();
+        data.extend(6u32.to_be_bytes());
+        data.extend(1u16.to_be_bytes());
+        data.extend((tracks.len() as u16).to_be_bytes());
+        data.extend(division.to_be_bytes());
+        for track in tracks {
+            data.extend(b"MTrk");
+            data.extend((track.len() as u32).to_be_bytes());
+            data.extend(*track);
+        }
+        data
+    }
+
+    fn parse(tracks: &[&[u8]]) -> Result<MidiScore, MidiError> {
+        parse_midi(&smf(480, tracks), &MidiImportOptions::default())
+    }
+
+    /// Onset, duration and key of every note.
+    fn spans(score: &MidiScore) -> Vec<(Time, Time, f32)> {
+        score
+            .notes
+            .iter()
+            .map(|note| (note.start, note.duration, note.pitch.0))
+            .collect()
+    }
+
+    #[test]
+    fn running_status() {
+        let score = parse(&[&[
+            0x00, 0x90, 60, 100, // C4 on
+            0x83, 0x60, 60, 0, // off, as a running note-on with velocity 0
+            0x00, 64, 80, // E4 on, still running
+            0x83, 0x60, 0x80, 64, 0, // off
+            0x00, 0xff, 0x2f, 0x00,
+        ]])
+        .expect("valid file");
+
+        assert_eq!(
+            spans(&score),
+            [
+                (Time::Ticks(0), Time::Ticks(480), 60.0),
+                (Time::Ticks(480), Time::Ticks(480), 64.0),
+            ]
+        );
+        assert!((score.notes[1].velocity - 80.0 / 127.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unreleased_notes_end_with_track() {
+        let score = parse(&[
+            &[0x00, 0x90, 60, 100, 0x87, 0x40, 0xff, 0x2f, 0x00],
+            &[0x83, 0x60, 0x91, 62, 100, 0x83, 0x60, 0xff, 0x2f, 0x00],
+        ])
+        .expect("valid file");
+
+        assert_eq!(
+            spans(&score),
+            [
+                (Time::Ticks(0), Time::Ticks(960), 60.0),
+                (Time::Ticks(480), Time::Ticks(480), 62.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn tempo_changes() {
+        let score = parse(&[&[
+            0x00, 0xff, 0x51, 0x03, 0x07, 0xa1, 0x20, // 120 BPM
+            0x00, 0x90, 60, 100, // C4 on
+            0x83, 0x60, 0x80, 60, 0, // off
+            0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40, // 60 BPM
+            0x00, 0x90, 62, 100, // D4 on
+            0x83, 0x60, 0x80, 62, 0, // off
+            0x00, 0xff, 0x2f, 0x00,
+        ]])
+        .expect("valid file");
+
+        let seconds = score
+            .instances
+            .iter()
+            .map(|instance| score.tempo.span(instance.start, instance.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(seconds, [(0.0, 0.5), (0.5, 1.0)]);
+    }
+
+    #[test]
+    fn lyrics() {
+        let score = parse(&[&[
+            0x00, 0xff, 0x05, 0x02, b'k', b'a', // lyric
+            0x00, 0x90, 60, 100, // C4 on
+            0x83, 0x60, 0x80, 60, 0, // off
+            0x00, 0xff, 0x2f, 0x00,
+        ]])
+        .expect("valid file");
+        assert_eq!(score.notes[0].phonemes, parse_ipa("ka").expect("valid IPA"));
+        assert_eq!(score.instances.len(), 2);
+
+        let err = parse(&[&[
+            0x00, 0xff, 0x05, 0x01, b'#', // lyric
+            0x00, 0x90, 60, 100, // C4 on
+            0x83, 0x60, 0x80, 60, 0, // off
+            0x00, 0xff, 0x2f, 0x00,
+        ]]);
+        assert!(matches!(
+            err,
+            Err(MidiError::UnknownLyric { tick: 0, text }) if text == "#"
+        ));
+    }
+
+    #[test]
+    fn malformed() {
+        let mut data = smf(480, &[&[0x00, 0x90, 60, 100]]);
+        data.truncate(data.len() - 1);
+        assert!(matches!(
+            parse_midi(&data, &MidiImportOptions::default()),
+            Err(MidiError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse(&[&[0x00, 60, 100]]),
+            Err(MidiError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn lyrics_stay_on_their_track() {
+        let score = parse(&[
+            &[
+                0x00, 0xff, 0x05, 0x02, b'k', b'a', // lyric
+                0x00, 0x90, 60, 100, // C4 on
+                0x83, 0x60, 0x80, 60, 0, // off
+                0x00, 0xff, 0x2f, 0x00,
+            ],
+            &[
+                0x00, 0xff, 0x05, 0x02, b's', b'i', // lyric at the same tick
+                0x00, 0x91, 64, 100, // E4 on
+                0x83, 0x60, 0x81, 64, 0, // off
+                0x00, 0xff, 0x2f, 0x00,
+            ],
+            // A note without a lyric on a third track.
+            &[
+                0x00, 0x92, 67, 100, 0x83, 0x60, 0x82, 67, 0, 0x00, 0xff, 0x2f, 0x00,
+            ],
+        ])
+        .expect("valid file");
+
+        let phonemes = score
+            .notes
+            .iter()
+            .map(|note| (note.pitch.0, note.phonemes.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            phonemes,
+            [
+                (60.0, parse_ipa("ka").expect("valid IPA")),
+                (64.0, parse_ipa("si").expect("valid IPA")),
+                (67.0, vec![MidiImportOptions::default().default_phoneme]),
+            ]
+        );
+    }
+
+    #[test]
+    fn controllers_of_other_channels_are_ignored() {
+        let score = parse(&[
+            &[
+                0x00, 0x90, 60, 100, // C4 on, channel 1
+                0x81, 0x70, 0xe0, 0x00, 0x60, // wheel up a quarter, channel 1
+                0x81, 0x70, 0x80, 60, 0, // off
+                0x00, 0xff, 0x2f, 0x00,
+            ],
+            &[
+                0x00, 0xe9, 0x00, 0x00, // wheel fully down, channel 10
+                0x00, 0xb9, 11, 0, // expression off, channel 10
+                0x83, 0x60, 0xb9, 11, 64, // expression half, channel 10
+                0x00, 0xff, 0x2f, 0x00,
+            ],
+        ])
+        .expect("valid file");
+
+        let bend = score
+            .pitch_bend
+            .points
+            .iter()
+            .map(|point| (point.time, point.value))
+            .collect::<Vec<_>>();
+        assert_eq!(bend, [(Time::Ticks(0), 0.0), (Time::Ticks(240), 100.0)]);
+        assert!(score.dynamics.points.is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+//! Scores: notes with lyrics, and importing them from other formats.
+
+pub mod midi;
+pub mod note;
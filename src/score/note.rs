@@ -0,0 +1,381 @@
+//! Notes with lyrics, and lowering them to phonemes.
+
+use std::ops::RangeInclusive;
+
+use crate::{
+    audio::MidiNote,
+    phoneme::{
+        ipa::Phoneme,
+        parse::{ParseError, parse_ipa},
+    },
+    scheduling::{
+        InstanceId, PhonemeInstance, PhonemeOptions,
+        time::{TempoMap, Time},
+    },
+};
+
+/// A syllable sung at a pitch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    /// Phonemes of the syllable, in order. A note without any is a rest.
+    pub phonemes: Vec<Phoneme>,
+    /// The note to sing at.
+    pub pitch: MidiNote,
+    /// Onset on the score timeline.
+    pub start: Time,
+    /// How long the note lasts, from its onset.
+    pub duration: Time,
+    /// How loud to sing, as an amplitude factor from 0 to 1.
+    pub velocity: f32,
+    /// Rendering options of the note as a whole, shared out between its
+    /// phonemes by [`lower_notes`].
+    pub options: PhonemeOptions,
+}
+
+impl Note {
+    /// A note singing `phonemes` at full velocity with default options.
+    pub fn new(phonemes: Vec<Phoneme>, pitch: MidiNote, start: Time, duration: Time) -> Self {
+        Self {
+            phonemes,
+            pitch,
+            start,
+            duration,
+            velocity: 1.0,
+            options: PhonemeOptions::default(),
+        }
+    }
+
+    /// A note singing the syllable written in IPA as `lyric`, such as `"kɑt"`.
+    /// Spaces are ignored; an empty lyric makes a rest.
+    ///
+    /// # Errors
+    /// Returns a [`ParseError`] if the lyric isn't valid IPA.
+    pub fn from_ipa(
+        lyric: &str,
+        pitch: MidiNote,
+        start: Time,
+        duration: Time,
+    ) -> Result<Self, ParseError> {
+        Ok(Self::new(parse_ipa(lyric)?, pitch, start, duration))
+    }
+
+    /// Whether the note is a rest.
+    pub fn is_rest(&self) -> bool {
+        self.phonemes.iter().all(|&p| p == Phoneme::Space)
+    }
+}
+
+/// Options for [`lower_notes`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoweringOptions {
+    /// Length of each consonant, in seconds.
+    pub consonant_length: f64,
+    /// Largest share of a note its consonants may take. Longer consonants are
+    /// shortened proportionally to fit.
+    pub max_consonant_share: f64,
+}
+
+impl Default for LoweringOptions {
+    /// 60 ms consonants taking at most half the note.
+    fn default() -> Self {
+        Self {
+            consonant_length: 0.06,
+            max_consonant_share: 0.5,
+        }
+    }
+}
+
+/// Distributes the phonemes of each note over its duration.
+///
+/// The nucleus of a syllable is its vowels, or its sonorants if it has none,
+/// and starts on the note's onset. Onset consonants before it lead into the
+/// note, ending on the onset, and coda consonants after it end with the note,
+/// each lasting `options.consonant_length`; the nucleus takes the remainder,
+/// shared evenly between its phonemes. The onset consonants take their time
+/// from the phoneme before, leaving at least half of it; where there isn't
+/// room, such as at the very start, the nucleus starts late by the
+/// difference. A syllable with neither vowels nor sonorants is shared evenly.
+/// Rests become a single [`Phoneme::Space`].
+///
+/// The note's options are shared out so that it still sounds like one note:
+/// the first phoneme gets the preutterance and overlap and the last the
+/// transition into the next note, the nucleus gets the vibrato, and the
+/// phonemes are legato in between, so the envelope spans the whole note.
+/// Phonemes without a preutterance or overlap take their own from the voice.
+///
+/// The instances' times are in seconds, resolved through `tempo`.
+pub fn lower_notes(
+    notes: &[Note],
+    tempo: &TempoMap,
+    options: &LoweringOptions,
+) -> Vec<PhonemeInstance> {
+    let mut out = Vec::<PhonemeInstance>::new();
+    for note in notes {
+        let (start, duration) = tempo.span(note.start, note.duration);
+        let phonemes = note
+            .phonemes
+            .iter()
+            .copied()
+            .filter(|&p| p != Phoneme::Space)
+            .collect::<Vec<_>>();
+        let phonemes = if phonemes.is_empty() {
+            vec![Phoneme::Space]
+        } else {
+            phonemes
+        };
+
+        // The onset consonants can reach back to halfway through the previous
+        // phoneme, which is shortened to make room for them.
+        let earliest = out.last().map_or(0.0, |last| {
+            tempo.seconds(last.start) + tempo.seconds(last.duration) / 2.0
+        });
+
+        let nucleus = nucleus(&phonemes);
+        let mut lead = 0.0;
+        let lengths = match &nucleus {
+            Some(nucleus) => {
+                let nucleus_len = nucleus.end() - nucleus.start() + 1;
+                let consonants = phonemes.len() - nucleus_len;
+                let consonant = if consonants == 0 {
+                    0.0
+                } else {
+                    options.consonant_length.max(0.0).min(
+                        duration * options.max_consonant_share.clamp(0.0, 1.0) / consonants as f64,
+                    )
+                };
+                let onset_len = consonant * *nucleus.start() as f64;
+                lead = onset_len.min((start - earliest).max(0.0));
+                // Whatever the onset can't lead by comes out of the nucleus.
+                let vowel = (duration - consonant * consonants as f64 + lead) / nucleus_len as f64;
+                (0..phonemes.len())
+                    .map(|i| {
+                        if nucleus.contains(&i) {
+                            vowel
+                        } else {
+                            consonant
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }
+            None => vec![duration / phonemes.len() as f64; phonemes.len()],
+        };
+
+        let mut onset = start - lead;
+        if let Some(prev) = out.last_mut() {
+            let prev_start = tempo.seconds(prev.start);
+            if prev_start + tempo.seconds(prev.duration) > onset {
+                prev.duration = Time::Seconds(onset - prev_start);
+            }
+        }
+        let last = phonemes.len() - 1;
+        for (i, (&phoneme, length)) in phonemes.iter().zip(lengths).enumerate() {
+            let in_nucleus = nucleus.as_ref().is_some_and(|n| n.contains(&i));
+            out.push(PhonemeInstance {
+                instance_id: InstanceId::new(out.len()),
+                phoneme,
+                start: Time::Seconds(onset),
+                duration: Time::Seconds(length),
+                options: PhonemeOptions {
+                    next_transition: note.options.next_transition.clone().filter(|_| i == last),
+                    vibrato: note.options.vibrato.clone().filter(|_| in_nucleus),
+                    envelope: note.options.envelope,
+                    preutterance: note.options.preutterance.filter(|_| i == 0),
+                    overlap: note.options.overlap.filter(|_| i == 0),
+                    legato_next: i < last || note.options.legato_next,
+                },
+                note: note.pitch,
+                velocity: note.velocity,
+            });
+            onset += length;
+        }
+    }
+    out
+}
+
+/// Range of indices of the syllable's nucleus: from its first vowel to its
+/// last, or failing that its first sonorant to its last.
+fn nucleus(phonemes: &[Phoneme]) -> Option<RangeInclusive<usize>> {
+    let span = |pred: fn(&Phoneme) -> bool| {
+        let first = phonemes.iter().position(pred)?;
+        let last = phonemes.iter().rposition(pred)?;
+        Some(first..=last)
+    };
+    span(|p| matches!(p, Phoneme::Vowel(_)))
+        .or_else(|| span(|p| matches!(p, Phoneme::Consonant(c) if c.is_sonorant())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduling::{
+        Schedule, TransitionOptions, dynamics::AdsrOptions, vibrato::VibratoOptions,
+    };
+
+    /// A one-second note on "kɑt" with every option set.
+    fn cat() -> Note {
+        let mut note = Note::from_ipa(
+            "kɑt",
+            MidiNote(60.0),
+            Time::Seconds(1.0),
+            Time::Seconds(1.0),
+        )
+        .expect("valid IPA");
+        note.options = PhonemeOptions {
+            next_transition: Some(TransitionOptions {
+                length_grains: 4,
+                portamento: None,
+            }),
+            vibrato: Some(VibratoOptions::default()),
+            envelope: Some(AdsrOptions::default()),
+            preutterance: Some(0.05),
+            overlap: Some(0.01),
+            legato_next: false,
+        };
+        note
+    }
+
+    fn seconds(time: Time) -> f64 {
+        TempoMap::default().seconds(time)
+    }
+
+    /// Start and duration of every instance.
+    fn spans(instances: &[PhonemeInstance]) -> Vec<(f64, f64)> {
+        instances
+            .iter()
+            .map(|i| (seconds(i.start), seconds(i.duration)))
+            .collect()
+    }
+
+    fn assert_spans(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-9 && (a.1 - e.1).abs() < 1e-9,
+                "{actual:?} isn't {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn nucleus_starts_on_the_onset() {
+        let instances = lower_notes(&[cat()], &TempoMap::default(), &LoweringOptions::default());
+        assert_spans(
+            &spans(&instances),
+            &[(0.94, 0.06), (1.0, 0.94), (1.94, 0.06)],
+        );
+    }
+
+    #[test]
+    fn onset_takes_time_from_the_previous_note() {
+        let a = Note::from_ipa("ɑ", MidiNote(60.0), Time::Seconds(0.0), Time::Seconds(1.0))
+            .expect("valid IPA");
+        let instances = lower_notes(
+            &[a, cat()],
+            &TempoMap::default(),
+            &LoweringOptions::default(),
+        );
+        assert_spans(
+            &spans(&instances),
+            &[(0.0, 0.94), (0.94, 0.06), (1.0, 0.94), (1.94, 0.06)],
+        );
+    }
+
+    #[test]
+    fn nucleus_is_late_without_room() {
+        // At the very start the onset can't lead at all.
+        let mut note = cat();
+        note.start = Time::Seconds(0.0);
+        let instances = lower_notes(&[note], &TempoMap::default(), &LoweringOptions::default());
+        assert_spans(
+            &spans(&instances),
+            &[(0.0, 0.06), (0.06, 0.88), (0.94, 0.06)],
+        );
+
+        // After a short phoneme it leads by half of that phoneme.
+        let a = Note::from_ipa(
+            "ɑ",
+            MidiNote(60.0),
+            Time::Seconds(0.96),
+            Time::Seconds(0.04),
+        )
+        .expect("valid IPA");
+        let instances = lower_notes(
+            &[a, cat()],
+            &TempoMap::default(),
+            &LoweringOptions::default(),
+        );
+        assert_spans(
+            &spans(&instances),
+            &[(0.96, 0.02), (0.98, 0.06), (1.04, 0.9), (1.94, 0.06)],
+        );
+    }
+
+    #[test]
+    fn consonants_are_shortened_to_fit() {
+        let mut note = cat();
+        note.duration = Time::Seconds(0.1);
+        let instances = lower_notes(&[note], &TempoMap::default(), &LoweringOptions::default());
+        assert_spans(
+            &spans(&instances),
+            &[(0.975, 0.025), (1.0, 0.075), (1.075, 0.025)],
+        );
+    }
+
+    #[test]
+    fn options_are_shared_out() {
+        let note = cat();
+        let instances = lower_notes(
+            std::slice::from_ref(&note),
+            &TempoMap::default(),
+            &LoweringOptions::default(),
+        );
+        assert_eq!(instances.len(), 3, "{instances:?}");
+        let (first, nucleus, last) = (&instances[0], &instances[1], &instances[2]);
+
+        assert_eq!(first.options.preutterance, note.options.preutterance);
+        assert_eq!(first.options.overlap, note.options.overlap);
+        for later in [nucleus, last] {
+            assert_eq!(later.options.preutterance, None);
+            assert_eq!(later.options.overlap, None);
+        }
+
+        assert_eq!(last.options.next_transition, note.options.next_transition);
+        assert_eq!(first.options.next_transition, None);
+        assert_eq!(nucleus.options.next_transition, None);
+
+        assert_eq!(nucleus.options.vibrato, note.options.vibrato);
+        assert_eq!(first.options.vibrato, None);
+        assert_eq!(last.options.vibrato, None);
+
+        assert!(
+            instances
+                .iter()
+                .all(|i| i.options.envelope == note.options.envelope)
+        );
+        assert!(first.options.legato_next && nucleus.options.legato_next);
+        assert!(!last.options.legato_next);
+    }
+
+    #[test]
+    fn rests_are_a_single_space() {
+        let rest = Note::new(
+            Vec::new(),
+            MidiNote(60.0),
+            Time::Seconds(0.0),
+            Time::Seconds(0.5),
+        );
+        assert!(rest.is_rest());
+        let instances = lower_notes(
+            &[rest, cat()],
+            &TempoMap::default(),
+            &LoweringOptions::default(),
+        );
+        assert_eq!(instances[0].phoneme, Phoneme::Space);
+        assert_eq!(instances.len(), 4);
+
+        let events = instances.schedule().events;
+        assert!(!events[0].legato_next);
+        assert!(events[1].legato_next && events[2].legato_next);
+        assert!(!events[3].legato_next);
+    }
+}